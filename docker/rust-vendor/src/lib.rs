@@ -0,0 +1 @@
+// 仅用于预编译依赖
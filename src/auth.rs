@@ -0,0 +1,266 @@
+use crate::config::{settings, AuthSettings, ClientSettings};
+use anyhow::Result;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use subtle::ConstantTimeEq;
+
+/// HMAC 签名方式使用的请求头
+pub const CLIENT_HEADER: &str = "x-neko-client";
+pub const TIMESTAMP_HEADER: &str = "x-neko-timestamp";
+pub const SIGNATURE_HEADER: &str = "x-neko-signature";
+pub const NONCE_HEADER: &str = "x-neko-nonce";
+
+/// nonce 的最大长度
+const MAX_NONCE_LEN: usize = 128;
+
+/// 按配置的客户端凭据认证请求
+///
+/// 支持两种方式：`authorization: Bearer <token>`，或者携带客户端 ID、Unix 时间戳（秒）、
+/// nonce 和签名四个请求头，签名为
+/// `HMAC-SHA256(hmac_secret, "<client>:<timestamp>:<nonce>:<method>")` 的十六进制，
+/// 其中 `method` 为 gRPC 方法路径，如 `/judge_grpc_service.JudgeGrpcService/Submit`。
+/// 时间戳与本机时间相差超过 `auth.max_clock_skew_secs` 的签名视为过期，
+/// 有效期内同一客户端的 nonce 只能使用一次，截获的签名无法重放。
+pub struct Authenticator {
+    clients: Vec<Arc<Client>>,
+    max_clock_skew_secs: u64,
+    nonces: Mutex<NonceCache>,
+}
+
+/// 已使用的 (客户端, nonce)，保留到对应签名不可能仍在有效期内为止
+#[derive(Default)]
+struct NonceCache {
+    used: HashSet<(String, String)>,
+    expiry: VecDeque<(u64, (String, String))>, // 按记录顺序排列的过期时间
+}
+
+impl Authenticator {
+    pub fn new(auth: &AuthSettings) -> Self {
+        Self {
+            clients: auth
+                .clients
+                .iter()
+                .map(|client| Arc::new(Client::new(client.clone())))
+                .collect(),
+            max_clock_skew_secs: auth.max_clock_skew_secs,
+            nonces: Mutex::new(NonceCache::default()),
+        }
+    }
+
+    /// 按全局配置创建，未启用认证时返回 `None`
+    pub fn from_settings() -> Option<Self> {
+        let auth = &settings().auth;
+        auth.enabled.then(|| Self::new(auth))
+    }
+
+    /// `method` 为请求的 gRPC 方法路径，`header` 按名称（小写）返回请求头的值
+    ///
+    /// 认证在拦截器中完成，只能看到请求头，看不到请求体。HMAC 签名只证明调用方、时间和方法，
+    /// 不覆盖请求内容：在 nonce 未被使用前截获的请求头可以配上其他内容重放。
+    /// 需要防止篡改内容时应启用 TLS。
+    pub fn authenticate(
+        &self,
+        method: &str,
+        header: impl Fn(&str) -> Option<String>,
+    ) -> Result<Arc<Client>> {
+        if let Some(authorization) = header("authorization") {
+            let token = authorization
+                .strip_prefix("Bearer ")
+                .ok_or_else(|| anyhow::anyhow!("Unsupported authorization scheme"))?;
+            // 逐个比较全部客户端，避免通过耗时推断令牌
+            let mut matched = None;
+            for client in &self.clients {
+                if let Some(expected) = &client.settings.token {
+                    if bool::from(expected.as_bytes().ct_eq(token.as_bytes())) {
+                        matched = Some(client.clone());
+                    }
+                }
+            }
+            return matched.ok_or_else(|| anyhow::anyhow!("Invalid token"));
+        }
+
+        let (Some(id), Some(timestamp), Some(nonce), Some(signature)) = (
+            header(CLIENT_HEADER),
+            header(TIMESTAMP_HEADER),
+            header(NONCE_HEADER),
+            header(SIGNATURE_HEADER),
+        ) else {
+            return Err(anyhow::anyhow!("Missing credentials"));
+        };
+        if nonce.is_empty() || nonce.len() > MAX_NONCE_LEN {
+            return Err(anyhow::anyhow!("Invalid nonce"));
+        }
+
+        let client = self
+            .clients
+            .iter()
+            .find(|client| client.settings.id == id)
+            .ok_or_else(|| anyhow::anyhow!("Unknown client {}", id))?;
+        let secret = client
+            .settings
+            .hmac_secret
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Client {} does not accept signed requests", id))?;
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let signed_at: u64 = timestamp
+            .parse()
+            .map_err(|_| anyhow::anyhow!("Invalid timestamp"))?;
+        if now.abs_diff(signed_at) > self.max_clock_skew_secs {
+            return Err(anyhow::anyhow!("Signature expired"));
+        }
+
+        let signature =
+            decode_hex(&signature).ok_or_else(|| anyhow::anyhow!("Invalid signature"))?;
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())?;
+        mac.update(signed_message(&id, signed_at, &nonce, method).as_bytes());
+        mac.verify_slice(&signature)
+            .map_err(|_| anyhow::anyhow!("Invalid signature"))?;
+
+        // 签名验证通过后才记录 nonce，未认证的请求无法占用缓存
+        // 时间戳最多比本机时间晚 max_clock_skew_secs，签名在记录后 2 倍偏差内一定过期
+        let mut nonces = self.nonces.lock().unwrap();
+        while let Some((expires_at, _)) = nonces.expiry.front() {
+            if *expires_at >= now {
+                break;
+            }
+            if let Some((_, key)) = nonces.expiry.pop_front() {
+                nonces.used.remove(&key);
+            }
+        }
+        let key = (id, nonce);
+        if !nonces.used.insert(key.clone()) {
+            return Err(anyhow::anyhow!("Nonce already used"));
+        }
+        let expires_at = now + 2 * self.max_clock_skew_secs;
+        nonces.expiry.push_back((expires_at, key));
+
+        Ok(client.clone())
+    }
+}
+
+/// 签名内容为 `client:timestamp:nonce:method`，不包含请求体
+fn signed_message(client: &str, timestamp: u64, nonce: &str, method: &str) -> String {
+    format!("{}:{}:{}:{}", client, timestamp, nonce, method)
+}
+
+/// 计算 HMAC 签名，供调用方和测试使用
+pub fn sign(secret: &str, client: &str, timestamp: u64, nonce: &str, method: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(signed_message(client, timestamp, nonce, method).as_bytes());
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// 已认证的客户端及其配额使用情况
+pub struct Client {
+    settings: ClientSettings,
+    active: AtomicUsize, // 正在排队或评测的提交数
+}
+
+impl Client {
+    fn new(settings: ClientSettings) -> Self {
+        Self {
+            settings,
+            active: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.settings.id
+    }
+
+    /// 检查单次提交的配额，通过后占用一个并发名额，直到返回的许可被释放
+    pub fn begin(
+        self: &Arc<Self>,
+        test_cases: usize,
+        time_limit_ms: u64,
+    ) -> Result<QuotaPermit, QuotaError> {
+        self.check_limits(test_cases, time_limit_ms)?;
+
+        let quota = &self.settings;
+        self.active
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |active| {
+                (quota.max_concurrent == 0 || active < quota.max_concurrent).then_some(active + 1)
+            })
+            .map_err(|_| {
+                QuotaError::ResourceExhausted(format!(
+                    "并发提交数已达上限 {}",
+                    quota.max_concurrent
+                ))
+            })?;
+
+        Ok(QuotaPermit {
+            client: self.clone(),
+        })
+    }
+
+    /// 只检查测试点数和总时间限制，用于已占用并发名额后才知道规模的请求
+    pub fn check_limits(&self, test_cases: usize, time_limit_ms: u64) -> Result<(), QuotaError> {
+        let quota = &self.settings;
+        if quota.max_test_cases > 0 && test_cases > quota.max_test_cases {
+            return Err(QuotaError::PermissionDenied(format!(
+                "测试点数 {} 超过上限 {}",
+                test_cases, quota.max_test_cases
+            )));
+        }
+
+        let total_time = time_limit_ms.saturating_mul(test_cases as u64);
+        if quota.max_total_time_ms > 0 && total_time > quota.max_total_time_ms {
+            return Err(QuotaError::PermissionDenied(format!(
+                "总时间限制 {} ms 超过上限 {} ms",
+                total_time, quota.max_total_time_ms
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// 配额检查失败：请求本身超出允许范围，或者并发名额暂时用尽
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuotaError {
+    PermissionDenied(String),
+    ResourceExhausted(String),
+}
+
+impl fmt::Display for QuotaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuotaError::PermissionDenied(message) | QuotaError::ResourceExhausted(message) => {
+                write!(f, "{}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for QuotaError {}
+
+/// 释放时归还并发名额
+pub struct QuotaPermit {
+    client: Arc<Client>,
+}
+
+impl Drop for QuotaPermit {
+    fn drop(&mut self) {
+        self.client.active.fetch_sub(1, Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,468 @@
+use clap::{Args, Parser, Subcommand};
+use opti_neko::judge::{
+    AnswerGenerator, CheckerSpec, Judge, JudgeConfig, JudgeResult, JudgeStatus, StressTest,
+    TestData, Validator,
+};
+use opti_neko::problem::import::{import_package, input_files};
+use opti_neko::problem::{PackageFormat, ProblemStore};
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{error, info};
+
+#[derive(Parser)]
+#[command(name = "opti-neko", about = "OptiOJ 判题服务")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+}
+
+#[derive(Subcommand)]
+pub enum Commands {
+    /// 启动 gRPC 判题服务（默认）
+    Serve,
+    /// 导入 Polygon/FPS/HUSTOJ 题目包到本地题目数据目录
+    Import {
+        /// 题目包格式：polygon、fps 或 hustoj
+        #[arg(long)]
+        format: PackageFormat,
+        /// 题目 ID
+        #[arg(long)]
+        problem_id: String,
+        /// 数据版本，省略时根据题目配置和数据内容生成
+        #[arg(long, default_value = "")]
+        version: String,
+        /// 题目包路径：zip 压缩包、解压后的目录或 FPS XML 文件
+        path: PathBuf,
+    },
+    /// 在本地用 *.in/*.out 测试数据评测源代码，输出各测试点结果
+    Judge(JudgeArgs),
+    /// 对拍：用生成器生成输入，比较参考解与候选解的输出，报告第一个反例
+    Stress(StressArgs),
+    /// 用参考解为 *.in 输入生成答案，保存为题目的新数据版本
+    Answers(AnswersArgs),
+}
+
+#[derive(Args)]
+pub struct JudgeArgs {
+    /// 语言变体，如 cpp17、python3、java17
+    #[arg(long)]
+    pub language: String,
+    /// 时间限制，单位：毫秒
+    #[arg(long, default_value_t = 1000)]
+    pub time_limit: u64,
+    /// 内存限制，单位：MB
+    #[arg(long, default_value_t = 256)]
+    pub memory_limit: u64,
+    /// 以 JSON 格式输出结果
+    #[arg(long)]
+    pub json: bool,
+    /// 源代码文件
+    pub source: PathBuf,
+    /// 测试数据目录，按文件名配对 *.in/*.out，存在 spj.cc 等文件时作为特殊评测程序
+    pub tests: PathBuf,
+}
+
+#[derive(Args)]
+pub struct StressArgs {
+    /// 生成器源文件，以种子作为唯一的命令行参数运行
+    #[arg(long)]
+    pub generator: PathBuf,
+    /// 参考解源文件，其输出作为标准答案
+    #[arg(long)]
+    pub reference: PathBuf,
+    /// 候选解源文件，可指定多个
+    #[arg(long = "candidate", required = true)]
+    pub candidates: Vec<PathBuf>,
+    /// 对拍次数
+    #[arg(long, default_value_t = 100)]
+    pub count: u64,
+    /// 起始种子，依次使用 seed..seed+count
+    #[arg(long, default_value_t = 1)]
+    pub seed: u64,
+    /// 使用已导入题目的 checker 和时空限制
+    #[arg(long)]
+    pub problem_id: Option<String>,
+    /// 时间限制，单位：毫秒
+    #[arg(long, default_value_t = 1000)]
+    pub time_limit: u64,
+    /// 内存限制，单位：MB
+    #[arg(long, default_value_t = 256)]
+    pub memory_limit: u64,
+    /// 以 JSON 格式输出结果
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Args)]
+pub struct AnswersArgs {
+    /// 参考解源文件
+    #[arg(long)]
+    pub reference: PathBuf,
+    /// 题目 ID
+    #[arg(long)]
+    pub problem_id: String,
+    /// 数据版本，省略时根据题目配置和数据内容生成
+    #[arg(long, default_value = "")]
+    pub version: String,
+    /// 时间限制，单位：毫秒
+    #[arg(long, default_value_t = 1000)]
+    pub time_limit: u64,
+    /// 内存限制，单位：MB
+    #[arg(long, default_value_t = 256)]
+    pub memory_limit: u64,
+    /// 输入数据目录，包含按文件名自然排序的 *.in 文件
+    pub inputs: PathBuf,
+}
+
+pub async fn import(
+    format: PackageFormat,
+    problem_id: &str,
+    version: &str,
+    path: &Path,
+) -> anyhow::Result<()> {
+    let package = import_package(format, path)?;
+
+    if let Some(validator) = &package.config.validator {
+        let errors = Validator::new(validator)
+            .validate_all(&package.test_cases)
+            .await?;
+        for e in &errors {
+            error!("测试点 #{} 校验失败: {}", e.test_case_id + 1, e.message);
+        }
+        if !errors.is_empty() {
+            return Err(anyhow::anyhow!(
+                "{} test case(s) failed validation",
+                errors.len()
+            ));
+        }
+    }
+
+    let manifest = ProblemStore::from_settings().save_package(problem_id, version, &package)?;
+
+    let config = &manifest.config;
+    info!(
+        problem_id = %manifest.problem_id,
+        version = %manifest.version,
+        name = %config.name,
+        test_case_count = %manifest.test_cases.len(),
+        subtask_count = %config.subtasks.len(),
+        checker = ?config.checker,
+        "题目包导入完成"
+    );
+    Ok(())
+}
+
+/// 本地评测，全部测试点通过时返回 `true`
+pub async fn judge(args: JudgeArgs) -> anyhow::Result<bool> {
+    let source_code = fs::read_to_string(&args.source)?;
+    // 与判题机上 HUSTOJ 格式的题目数据使用相同的配对和比较规则
+    let package = import_package(PackageFormat::Hustoj, &args.tests)?;
+
+    let config = JudgeConfig {
+        time_limit: Duration::from_millis(args.time_limit),
+        memory_limit: args.memory_limit * 1024 * 1024,
+        language: args.language,
+        source_code,
+    };
+    let result = Judge::new(config)
+        .with_checker(package.config.checker.clone())
+        .with_output_diff(true)
+        .judge_all(&package.test_cases)
+        .await?;
+
+    let names: Vec<String> = package
+        .test_cases
+        .iter()
+        .map(|test_case| match &test_case.input {
+            TestData::File(path) => path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            TestData::Inline(_) => String::new(),
+        })
+        .collect();
+
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&result_json(&result, &names))?
+        );
+    } else {
+        print_table(&result, &names);
+    }
+
+    Ok(result.status == JudgeStatus::Accepted)
+}
+
+fn result_json(result: &JudgeResult, names: &[String]) -> serde_json::Value {
+    let test_cases: Vec<_> = result
+        .test_case_results
+        .iter()
+        .map(|tcr| {
+            json!({
+                "id": tcr.test_case_id + 1,
+                "name": names.get(tcr.test_case_id),
+                "status": tcr.status,
+                "time_used": tcr.time_used.as_millis() as u64,
+                "memory_used": tcr.memory_used / 1024,
+                "output_diff": tcr.output_diff,
+            })
+        })
+        .collect();
+
+    json!({
+        "status": result.status,
+        "time_used": result.time_used.as_millis() as u64, // 单位：毫秒
+        "memory_used": result.memory_used / 1024, // 单位：KB
+        "error_message": result.error_message,
+        "test_cases": test_cases,
+    })
+}
+
+fn print_table(result: &JudgeResult, names: &[String]) {
+    if let Some(message) = &result.error_message {
+        println!("{}", message.trim_end());
+        println!();
+    }
+
+    if !result.test_case_results.is_empty() {
+        println!(
+            "{:>4}  {:<12}  {:<20}  {:>10}  {:>10}",
+            "#", "Test", "Verdict", "Time(ms)", "Memory(KB)"
+        );
+        for tcr in &result.test_case_results {
+            println!(
+                "{:>4}  {:<12}  {:<20}  {:>10}  {:>10}",
+                tcr.test_case_id + 1,
+                names
+                    .get(tcr.test_case_id)
+                    .map(String::as_str)
+                    .unwrap_or(""),
+                format!("{:?}", tcr.status),
+                tcr.time_used.as_millis(),
+                tcr.memory_used / 1024
+            );
+        }
+        println!();
+    }
+
+    // 只展示第一个答案错误的测试点的差异
+    if let Some((tcr, diff)) = result
+        .test_case_results
+        .iter()
+        .find_map(|tcr| Some((tcr, tcr.output_diff.as_ref()?)))
+    {
+        println!(
+            "测试点 {} 第 {} 行第 {} 列与答案不一致:",
+            tcr.test_case_id + 1,
+            diff.line,
+            diff.column
+        );
+        print!("{}", diff.unified_diff);
+        println!();
+    }
+
+    let passed = result
+        .test_case_results
+        .iter()
+        .filter(|tcr| tcr.status == JudgeStatus::Accepted)
+        .count();
+    println!(
+        "最终结果: {:?}  通过: {}/{}  最大时间: {} ms  最大内存: {} KB",
+        result.status,
+        passed,
+        result.test_case_results.len(),
+        result.time_used.as_millis(),
+        result.memory_used / 1024
+    );
+}
+
+/// 生成器的时间限制，数据规模较大时生成可能比选手程序慢得多
+const GENERATOR_TIME_LIMIT: Duration = Duration::from_secs(10);
+
+/// 对拍，没有找到反例时返回 `true`
+pub async fn stress(args: StressArgs) -> anyhow::Result<bool> {
+    let mut time_limit = Duration::from_millis(args.time_limit);
+    let mut memory_limit = args.memory_limit * 1024 * 1024;
+    let mut checker = CheckerSpec::Exact;
+    if let Some(problem_id) = &args.problem_id {
+        let manifest = ProblemStore::from_settings().load(problem_id, None)?;
+        let config = manifest.config;
+        if let Some(ms) = config.time_limit_ms {
+            time_limit = Duration::from_millis(ms);
+        }
+        if let Some(mb) = config.memory_limit_mb {
+            memory_limit = mb * 1024 * 1024;
+        }
+        checker = config.checker;
+    }
+
+    let program = |path: &Path, time_limit: Duration| -> anyhow::Result<JudgeConfig> {
+        Ok(JudgeConfig {
+            time_limit,
+            memory_limit,
+            language: language_of(path)?.to_string(),
+            source_code: fs::read_to_string(path)?,
+        })
+    };
+    let candidates = args
+        .candidates
+        .iter()
+        .map(|path| program(path, time_limit))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let stress = StressTest::new(
+        program(&args.generator, GENERATOR_TIME_LIMIT.max(time_limit))?,
+        program(&args.reference, time_limit)?,
+        candidates,
+    )
+    .with_checker(checker);
+
+    let report = stress.run(args.seed..args.seed + args.count).await?;
+    let counterexample = report.counterexample.as_ref();
+
+    if args.json {
+        let value = json!({
+            "tests_run": report.tests_run,
+            "counterexample": counterexample.map(|c| json!({
+                "seed": c.seed,
+                "candidate": args.candidates[c.candidate].display().to_string(),
+                "status": c.status,
+                "input": String::from_utf8_lossy(&c.input),
+                "expected_output": String::from_utf8_lossy(&c.expected_output),
+                "actual_output": String::from_utf8_lossy(&c.actual_output),
+            })),
+        });
+        println!("{}", serde_json::to_string_pretty(&value)?);
+    } else {
+        match counterexample {
+            Some(c) => {
+                println!(
+                    "第 {} 组数据（种子 {}）发现反例: {} 结果为 {:?}",
+                    report.tests_run,
+                    c.seed,
+                    args.candidates[c.candidate].display(),
+                    c.status
+                );
+                println!("\n输入:\n{}", String::from_utf8_lossy(&c.input).trim_end());
+                println!(
+                    "\n参考解输出:\n{}",
+                    String::from_utf8_lossy(&c.expected_output).trim_end()
+                );
+                println!(
+                    "\n候选解输出:\n{}",
+                    String::from_utf8_lossy(&c.actual_output).trim_end()
+                );
+            }
+            None => println!("{} 组数据全部一致", report.tests_run),
+        }
+    }
+
+    Ok(counterexample.is_none())
+}
+
+/// 生成答案并保存，参考解全部正常结束时返回 `true`
+pub async fn answers(args: AnswersArgs) -> anyhow::Result<bool> {
+    let inputs: Vec<TestData> = input_files(&args.inputs)?
+        .into_iter()
+        .map(TestData::File)
+        .collect();
+    if inputs.is_empty() {
+        return Err(anyhow::anyhow!("No .in files found"));
+    }
+
+    let store = ProblemStore::from_settings();
+    let mut upload = store.begin_upload(&args.problem_id, &args.version)?;
+    // 沿用题目当前的 checker、子任务等配置
+    upload.set_config(store.current_config(&args.problem_id)?);
+    for (id, input) in inputs.iter().enumerate() {
+        upload.write_data(id, false, input)?;
+    }
+
+    // 先用题目的校验程序检查输入，不合法的输入不运行参考解
+    if let Some(validator) = &upload.config().validator {
+        let errors = Validator::new(validator)
+            .validate_all(&upload.test_cases(inputs.len()))
+            .await?;
+        for e in &errors {
+            error!("测试点 #{} 校验失败: {}", e.test_case_id + 1, e.message);
+        }
+        if !errors.is_empty() {
+            return Err(anyhow::anyhow!(
+                "{} test case(s) failed validation",
+                errors.len()
+            ));
+        }
+    }
+
+    let reference = JudgeConfig {
+        time_limit: Duration::from_millis(args.time_limit),
+        memory_limit: args.memory_limit * 1024 * 1024,
+        language: language_of(&args.reference)?.to_string(),
+        source_code: fs::read_to_string(&args.reference)?,
+    };
+    let runs = AnswerGenerator::new(reference)
+        .generate(&inputs, |id, output| {
+            upload.write_data(id, true, &TestData::File(output.to_path_buf()))
+        })
+        .await?;
+
+    println!(
+        "{:>4}  {:<12}  {:<20}  {:>10}  {:>10}",
+        "#", "Test", "Status", "Time(ms)", "Memory(KB)"
+    );
+    for (id, run) in runs.iter().enumerate() {
+        let name = match &inputs[id] {
+            TestData::File(path) => path.file_stem().unwrap_or_default().to_string_lossy(),
+            TestData::Inline(_) => Default::default(),
+        };
+        println!(
+            "{:>4}  {:<12}  {:<20}  {:>10}  {:>10}",
+            id + 1,
+            name,
+            format!("{:?}", run.status),
+            run.time_used.as_millis(),
+            run.memory_used / 1024
+        );
+    }
+    println!();
+
+    if runs.iter().any(|run| run.status != JudgeStatus::Accepted) {
+        println!("参考解存在超时或运行错误，答案未保存");
+        return Ok(false);
+    }
+
+    let manifest = upload.finish(inputs.len(), &[])?;
+    println!(
+        "已保存题目 {} 的数据版本 {}，共 {} 个测试点",
+        manifest.problem_id,
+        manifest.version,
+        manifest.test_cases.len()
+    );
+    Ok(true)
+}
+
+/// 按源文件扩展名推断语言变体
+fn language_of(path: &Path) -> anyhow::Result<&'static str> {
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    Ok(match extension.as_str() {
+        "c" => "c11",
+        "cc" | "cpp" | "cxx" => "cpp17",
+        "py" => "python3",
+        "java" => "java17",
+        "go" => "go",
+        "rs" => "rust",
+        "js" => "javascript",
+        "ts" => "typescript",
+        _ => {
+            return Err(anyhow::anyhow!(
+                "Cannot infer language of {}",
+                path.display()
+            ))
+        }
+    })
+}
@@ -0,0 +1,437 @@
+use crate::judge::language::LANGUAGE_VARIANTS;
+use anyhow::Result;
+use config::{Config, Environment, File};
+use serde::Deserialize;
+use std::env;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// 判题机全局配置
+///
+/// 依次读取 `config/neko.toml`（可通过 `NEKO_CONFIG` 指定其他路径）和
+/// `NEKO_` 前缀的环境变量，层级之间用 `__` 分隔，例如 `NEKO_RUST__EDITION=2018`。
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub judge: JudgeSettings,
+    pub c: GccSettings,
+    pub cpp: GccSettings,
+    pub rust: RustSettings,
+    pub javascript: JavaScriptSettings,
+    pub compile: CompileSettings,
+    pub sandbox: SandboxSettings,
+    pub cache: CacheSettings,
+    pub problem: ProblemSettings,
+    pub checker: CheckerSettings,
+    pub validator: ValidatorSettings,
+    pub queue: QueueSettings,
+    pub metrics: MetricsSettings,
+    pub log: LogSettings,
+    pub server: ServerSettings,
+    pub health: HealthSettings,
+    pub languages: LanguagesSettings,
+    pub auth: AuthSettings,
+    pub limits: LimitsSettings,
+    pub store: StoreSettings,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct JudgeSettings {
+    pub output_limit_kb: u64,      // 返回给调用方的实际输出长度上限(KB)
+    pub output_file_limit_mb: u64, // 程序 stdout、stderr 文件的大小上限(MB)
+    pub diff_context_lines: usize, // 输出差异中不一致处前后保留的相同行数
+    pub diff_max_bytes: usize,     // 输出差异文本的长度上限
+}
+
+impl Default for JudgeSettings {
+    fn default() -> Self {
+        Self {
+            output_limit_kb: 64,
+            output_file_limit_mb: 64,
+            diff_context_lines: 3,
+            diff_max_bytes: 4096,
+        }
+    }
+}
+
+/// C 和 C++ 共用的编译选项，分别位于 `[c]` 和 `[cpp]`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct GccSettings {
+    pub opt_level: String, // gcc/g++ -O
+}
+
+impl Default for GccSettings {
+    fn default() -> Self {
+        Self {
+            opt_level: "2".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RustSettings {
+    pub edition: String,             // rustc --edition
+    pub opt_level: String,           // rustc -C opt-level
+    pub vendor_dir: Option<PathBuf>, // 离线预编译 crate 目录
+    pub vendor_crates: Vec<String>,  // 允许提交代码使用的 crate 名称
+}
+
+impl Default for RustSettings {
+    fn default() -> Self {
+        Self {
+            edition: "2021".to_string(),
+            opt_level: "2".to_string(),
+            vendor_dir: None,
+            vendor_crates: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct JavaScriptSettings {
+    pub tsc: String,          // TypeScript 编译器路径
+    pub time_multiplier: f64, // 时间限制倍数，补偿 JIT 预热
+    pub heap_reserve_mb: u64, // 预留给 V8 非堆内存的空间(MB)
+}
+
+impl Default for JavaScriptSettings {
+    fn default() -> Self {
+        Self {
+            tsc: "tsc".to_string(),
+            time_multiplier: 2.0,
+            heap_reserve_mb: 32,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CompileSettings {
+    pub time_limit_ms: u64,     // 编译时间限制(毫秒)
+    pub memory_limit_mb: u64,   // 编译内存限制(MB)
+    pub output_limit_kb: usize, // 编译器输出保留上限(KB)
+}
+
+impl Default for CompileSettings {
+    fn default() -> Self {
+        Self {
+            time_limit_ms: 10_000,
+            memory_limit_mb: 1024,
+            output_limit_kb: 64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SandboxSettings {
+    pub work_root: PathBuf, // 判题工作目录的根目录
+}
+
+impl Default for SandboxSettings {
+    fn default() -> Self {
+        Self {
+            work_root: env::temp_dir().join("opti-neko"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CacheSettings {
+    pub enabled: bool,    // 是否启用编译产物缓存
+    pub dir: PathBuf,     // 缓存目录
+    pub max_size_mb: u64, // 缓存总大小上限(MB)
+}
+
+impl Default for CacheSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            dir: env::temp_dir().join("opti-neko-cache"),
+            max_size_mb: 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ProblemSettings {
+    pub data_dir: PathBuf, // 题目测试数据仓库目录
+}
+
+impl Default for ProblemSettings {
+    fn default() -> Self {
+        Self {
+            data_dir: PathBuf::from("testcases"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CheckerSettings {
+    pub time_limit_ms: u64,            // 特殊评测程序单次运行时间限制(毫秒)
+    pub memory_limit_mb: u64,          // 特殊评测程序内存限制(MB)
+    pub testlib_path: Option<PathBuf>, // 编译特殊评测程序时提供的 testlib.h
+}
+
+impl Default for CheckerSettings {
+    fn default() -> Self {
+        Self {
+            time_limit_ms: 10_000,
+            memory_limit_mb: 512,
+            testlib_path: Some(PathBuf::from("/usr/local/include/testlib.h")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ValidatorSettings {
+    pub time_limit_ms: u64,   // 校验程序单次运行时间限制(毫秒)
+    pub memory_limit_mb: u64, // 校验程序内存限制(MB)
+}
+
+impl Default for ValidatorSettings {
+    fn default() -> Self {
+        Self {
+            time_limit_ms: 10_000,
+            memory_limit_mb: 512,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct QueueSettings {
+    pub workers: usize, // 同时执行的评测任务数
+}
+
+impl Default for QueueSettings {
+    fn default() -> Self {
+        Self { workers: 1 }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MetricsSettings {
+    pub enabled: bool,  // 是否提供 /metrics
+    pub listen: String, // 指标服务监听地址
+}
+
+impl Default for MetricsSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            listen: "0.0.0.0:8080".to_string(),
+        }
+    }
+}
+
+/// 日志输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct LogSettings {
+    pub format: LogFormat, // text 或 json
+    pub level: String,     // 日志过滤规则，设置了 RUST_LOG 时以其为准
+    pub test_data: bool,   // 是否在日志中记录测试数据和程序输出
+    pub source: bool,      // 是否在日志中记录源代码
+    pub max_bytes: usize,  // 记录测试数据、输出、源代码时保留的最大字节数
+}
+
+impl Default for LogSettings {
+    fn default() -> Self {
+        Self {
+            format: LogFormat::Text,
+            level: "info".to_string(),
+            test_data: false,
+            source: false,
+            max_bytes: 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ServerSettings {
+    pub listen: String,             // gRPC 服务监听地址
+    pub shutdown_timeout_secs: u64, // 退出时等待进行中评测任务结束的最长时间
+    pub tls: TlsSettings,
+}
+
+impl Default for ServerSettings {
+    fn default() -> Self {
+        Self {
+            listen: "0.0.0.0:50051".to_string(),
+            shutdown_timeout_secs: 30,
+            tls: TlsSettings::default(),
+        }
+    }
+}
+
+/// gRPC 服务的 TLS 配置，证书和私钥均为 PEM 格式
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TlsSettings {
+    pub enabled: bool,
+    pub cert_path: PathBuf,              // 服务端证书（可包含中间证书链）
+    pub key_path: PathBuf,               // 服务端私钥
+    pub client_ca_path: Option<PathBuf>, // 设置后要求客户端出示由该 CA 签发的证书（mTLS）
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct HealthSettings {
+    pub interval_secs: u64,     // 健康检查间隔
+    pub languages: Vec<String>, // 工具链必须可用的语言，任一不可用时报告 NOT_SERVING
+}
+
+impl Default for HealthSettings {
+    fn default() -> Self {
+        Self {
+            interval_secs: 30,
+            languages: vec!["cpp17".to_string(), "python3".to_string()],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct LanguagesSettings {
+    pub enabled: Vec<String>, // 启用的语言变体，启动时逐一自检，未通过的语言被禁用
+}
+
+impl Default for LanguagesSettings {
+    fn default() -> Self {
+        Self {
+            enabled: LANGUAGE_VARIANTS
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AuthSettings {
+    pub enabled: bool,
+    pub max_clock_skew_secs: u64, // HMAC 签名时间戳允许的最大偏差
+    pub clients: Vec<ClientSettings>,
+}
+
+impl Default for AuthSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_clock_skew_secs: 300,
+            clients: Vec::new(),
+        }
+    }
+}
+
+/// 客户端凭据与配额，配额为 0 表示不限制
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ClientSettings {
+    pub id: String,
+    pub token: Option<String>,       // Bearer 令牌
+    pub hmac_secret: Option<String>, // HMAC 签名密钥，签名不覆盖请求内容
+    pub max_concurrent: usize,       // 同时排队或评测的提交数
+    pub max_total_time_ms: u64,      // 单次提交的时间限制乘以测试点数
+    pub max_test_cases: usize,       // 单次提交的测试点数
+}
+
+/// 单次请求允许的最大规模，为 0 表示不限制
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct LimitsSettings {
+    pub max_time_limit_ms: u64,
+    pub max_memory_limit_mb: u64,
+    pub max_source_kb: u64,
+    pub max_test_cases: usize, // 请求中附带或上传的测试点数，不限制判题机上已同步的题目数据
+    pub max_input_mb: u64,     // 提交或运行请求中附带的输入数据总大小，同时决定 gRPC 消息大小上限
+    pub max_problem_mb: u64,   // 上传、导入题目或生成答案时的题目数据总大小
+}
+
+impl Default for LimitsSettings {
+    fn default() -> Self {
+        Self {
+            max_time_limit_ms: 30000,
+            max_memory_limit_mb: 2048,
+            max_source_kb: 256,
+            max_test_cases: 1000,
+            max_input_mb: 64,
+            max_problem_mb: 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct StoreSettings {
+    pub path: PathBuf, // 提交与评测结果数据库（SQLite）
+}
+
+impl Default for StoreSettings {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from("data/submissions.db"),
+        }
+    }
+}
+
+impl Settings {
+    pub fn load() -> Result<Self> {
+        let path = env::var("NEKO_CONFIG").unwrap_or_else(|_| "config/neko".to_string());
+
+        let settings = Config::builder()
+            .add_source(File::with_name(&path).required(false))
+            .add_source(
+                Environment::with_prefix("NEKO")
+                    .prefix_separator("_")
+                    .separator("__")
+                    .list_separator(",")
+                    .with_list_parse_key("rust.vendor_crates")
+                    .with_list_parse_key("health.languages")
+                    .with_list_parse_key("languages.enabled")
+                    .try_parsing(true),
+            )
+            .build()?
+            .try_deserialize()?;
+
+        Ok(settings)
+    }
+}
+
+static SETTINGS: OnceLock<Settings> = OnceLock::new();
+
+/// 加载全局配置，程序启动时调用
+///
+/// 配置文件或环境变量有误时返回错误，不会退回默认配置：默认配置关闭了认证和 TLS，
+/// 带着错误配置启动比拒绝启动更危险。
+pub fn init() -> Result<&'static Settings> {
+    if let Some(settings) = SETTINGS.get() {
+        return Ok(settings);
+    }
+    let settings = Settings::load()?;
+    Ok(SETTINGS.get_or_init(|| settings))
+}
+
+/// 获取全局配置，未调用 [`init`] 时在首次调用时加载，加载失败则 panic
+pub fn settings() -> &'static Settings {
+    SETTINGS.get_or_init(|| Settings::load().expect("failed to load configuration"))
+}
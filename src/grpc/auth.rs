@@ -0,0 +1,132 @@
+use opti_neko::auth::{Authenticator, Client, QuotaError, QuotaPermit};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tonic::codegen::http;
+use tonic::service::Interceptor;
+use tonic::{Request, Status};
+use tower::{Layer, Service};
+use tracing::warn;
+
+/// 请求的 gRPC 方法路径，如 `/judge_grpc_service.JudgeGrpcService/Submit`
+#[derive(Debug, Clone)]
+pub struct MethodPath(pub String);
+
+/// 把方法路径放入请求的 extensions，拦截器只能拿到 metadata 和 extensions，拿不到 URI
+#[derive(Debug, Clone, Default)]
+pub struct MethodPathLayer;
+
+impl<S> Layer<S> for MethodPathLayer {
+    type Service = MethodPathService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MethodPathService { inner }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MethodPathService<S> {
+    inner: S,
+}
+
+impl<S, B> Service<http::Request<B>> for MethodPathService<S>
+where
+    S: Service<http::Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: http::Request<B>) -> Self::Future {
+        let path = MethodPath(request.uri().path().to_string());
+        request.extensions_mut().insert(path);
+        self.inner.call(request)
+    }
+}
+
+/// 认证拦截器，通过后把客户端放入请求的 extensions，供配额检查使用
+#[derive(Clone)]
+pub struct AuthInterceptor {
+    authenticator: Option<Arc<Authenticator>>, // 未启用认证时为 None，请求直接放行
+}
+
+impl AuthInterceptor {
+    pub fn from_settings() -> Self {
+        Self {
+            authenticator: Authenticator::from_settings().map(Arc::new),
+        }
+    }
+}
+
+impl Interceptor for AuthInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        let Some(authenticator) = &self.authenticator else {
+            return Ok(request);
+        };
+
+        // 签名覆盖方法路径，截获的签名不能用于其他接口
+        let method = request
+            .extensions()
+            .get::<MethodPath>()
+            .map(|path| path.0.clone())
+            .unwrap_or_default();
+        let metadata = request.metadata();
+        let client = authenticator
+            .authenticate(&method, |name| {
+                metadata
+                    .get(name)
+                    .and_then(|value| value.to_str().ok())
+                    .map(str::to_string)
+            })
+            .map_err(|e| {
+                warn!("认证失败: {}", e);
+                Status::unauthenticated(e.to_string())
+            })?;
+        request.extensions_mut().insert(client);
+        Ok(request)
+    }
+}
+
+/// 检查已认证客户端的配额，未启用认证时不限制
+pub fn check_quota(
+    client: Option<&Arc<Client>>,
+    test_cases: usize,
+    time_limit_ms: u64,
+) -> Result<Option<QuotaPermit>, QuotaError> {
+    let Some(client) = client else {
+        return Ok(None);
+    };
+    client
+        .begin(test_cases, time_limit_ms)
+        .map(Some)
+        .map_err(|e| {
+            warn!(client = client.id(), "超出配额: {}", e);
+            e
+        })
+}
+
+/// 只检查测试点数和总时间限制，未启用认证时不限制
+pub fn check_limits(
+    client: Option<&Arc<Client>>,
+    test_cases: usize,
+    time_limit_ms: u64,
+) -> Result<(), QuotaError> {
+    let Some(client) = client else {
+        return Ok(());
+    };
+    client.check_limits(test_cases, time_limit_ms).map_err(|e| {
+        warn!(client = client.id(), "超出配额: {}", e);
+        e
+    })
+}
+
+/// 请求超出允许范围时返回 PERMISSION_DENIED，并发名额用尽时返回 RESOURCE_EXHAUSTED
+pub fn quota_status(error: QuotaError) -> Status {
+    match error {
+        QuotaError::PermissionDenied(message) => Status::permission_denied(message),
+        QuotaError::ResourceExhausted(message) => Status::resource_exhausted(message),
+    }
+}
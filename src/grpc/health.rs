@@ -0,0 +1,41 @@
+use crate::grpc::judge_grpc_service::judge_grpc_service_server::JudgeGrpcServiceServer;
+use crate::grpc::service::JudgeGrpcServiceImpl;
+use opti_neko::config::settings;
+use opti_neko::judge::health;
+use std::time::Duration;
+use tonic::server::NamedService;
+use tonic_health::server::HealthReporter;
+use tonic_health::ServingStatus;
+use tracing::{info, warn};
+
+/// 同时更新整体状态（空服务名）和判题服务的状态
+pub async fn set_status(reporter: &mut HealthReporter, status: ServingStatus) {
+    reporter.set_service_status("", status).await;
+    reporter
+        .set_service_status(
+            <JudgeGrpcServiceServer<JudgeGrpcServiceImpl> as NamedService>::NAME,
+            status,
+        )
+        .await;
+}
+
+/// 定期检查沙箱和工具链，不可用时报告 NOT_SERVING
+pub async fn monitor(mut reporter: HealthReporter) {
+    let interval = Duration::from_secs(settings().health.interval_secs.max(1));
+    let mut last = None;
+    loop {
+        let status = match health::check().await {
+            Ok(()) => ServingStatus::Serving,
+            Err(e) => {
+                warn!("判题环境不可用: {}", e);
+                ServingStatus::NotServing
+            }
+        };
+        if last != Some(status) {
+            info!(status = ?status, "健康状态变化");
+            last = Some(status);
+        }
+        set_status(&mut reporter, status).await;
+        tokio::time::sleep(interval).await;
+    }
+}
@@ -1,4 +1,8 @@
+pub mod auth;
+pub mod health;
 pub mod service;
+pub mod tasks;
+pub mod tls;
 
 pub mod judge_grpc_service {
     tonic::include_proto!("judge_grpc_service");
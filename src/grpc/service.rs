@@ -1,31 +1,157 @@
+use crate::grpc::auth::{check_limits, check_quota, quota_status};
+use crate::grpc::judge_grpc_service::generate_answers_request::Payload as AnswersPayload;
+use crate::grpc::judge_grpc_service::import_problem_request::Payload as ImportPayload;
 use crate::grpc::judge_grpc_service::judge_grpc_service_server::JudgeGrpcService;
+use crate::grpc::judge_grpc_service::rejudge_request::Target as RejudgeTarget;
+use crate::grpc::judge_grpc_service::upload_problem_request::Payload;
 use crate::grpc::judge_grpc_service::{
-    SubmitRequest, SubmitResponse, TestCase as GrpcTestCase, TestCaseResult as GrpcTestCaseResult,
+    GenerateAnswersRequest, GenerateAnswersResponse, GetProblemVersionRequest,
+    GetProblemVersionResponse, GetQueueStatusRequest, GetQueueStatusResponse, GetSubmissionRequest,
+    GetSubmissionResponse, ImportProblemRequest, ImportProblemResponse, JudgeRun as GrpcJudgeRun,
+    LanguageInfo, ListLanguagesRequest, ListLanguagesResponse, OutputDiff as GrpcOutputDiff,
+    Priority as GrpcPriority, QueuedTask as GrpcQueuedTask, ReferenceResult, RejudgeRequest,
+    RejudgeResponse, RejudgeResult, RunRequest, RunResponse, SubmitRequest, SubmitResponse,
+    TestCase as GrpcTestCase, TestCaseChange as GrpcTestCaseChange,
+    TestCaseResult as GrpcTestCaseResult, UploadProblemRequest, UploadProblemResponse,
+    ValidationError as GrpcValidationError, ValidatorProgram as GrpcValidatorProgram,
+    VerdictTransition as GrpcVerdictTransition,
 };
-use opti_neko::judge::{Judge, JudgeConfig, TestCase, TestCaseResult};
+use crate::grpc::tasks::RunningTasks;
+use opti_neko::auth::Client;
+use opti_neko::config::settings;
+use opti_neko::judge::toolchain;
+use opti_neko::judge::{
+    AnswerGenerator, CheckerSpec, Judge, JudgeConfig, JudgeResult, JudgeStatus, TestCase,
+    TestCaseResult, TestData, ValidationError, Validator, ValidatorSpec,
+};
+use opti_neko::limits::{check_problem_data, check_test_case_id, RequestSize};
+use opti_neko::logging;
+use opti_neko::metrics::metrics;
+use opti_neko::problem::import::{import_package, Workspace};
+use opti_neko::problem::{
+    PackageFormat, ProblemConfig, ProblemManifest, ProblemStore, VersionConflict,
+};
+use opti_neko::queue::{JudgeQueue, Priority, QueuedTask, TaskInfo};
+use opti_neko::submission::{
+    self, RejudgeEntry, RejudgeReport, ResultDiff, Submission, SubmissionStore,
+};
+use std::future::Future;
+use std::io::Write;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::Mutex;
-use tonic::{Request, Response, Status};
-use tracing::{error, info};
+use tokio::sync::Semaphore;
+use tonic::{Request, Response, Status, Streaming};
+use tracing::{error, info, info_span, warn, Instrument};
 
+#[derive(Clone)]
 pub struct JudgeGrpcServiceImpl {
-    judge: Arc<Mutex<Judge>>,
+    queue: JudgeQueue,
+    problems: Arc<ProblemStore>,
+    submissions: Arc<SubmissionStore>,
+    tasks: RunningTasks,
 }
 
-impl Default for JudgeGrpcServiceImpl {
-    fn default() -> Self {
+impl JudgeGrpcServiceImpl {
+    pub fn new() -> anyhow::Result<Self> {
         info!("创建新的 JudgeGrpcServiceImpl 实例");
-        let default_config = JudgeConfig {
-            time_limit: Duration::from_secs(1),
-            memory_limit: 256 * 1024 * 1024,
-            language: String::new(),
-            source_code: String::new(),
-        };
+        Ok(Self {
+            queue: JudgeQueue::from_settings(),
+            problems: Arc::new(ProblemStore::from_settings()),
+            submissions: Arc::new(SubmissionStore::from_settings()?),
+            tasks: RunningTasks::default(),
+        })
+    }
 
-        Self {
-            judge: Arc::new(Mutex::new(Judge::new(default_config))),
+    /// 中止仍在执行的任务并等待它们结束，被中止的提交记录一次系统错误结果
+    ///
+    /// 用于退出时等待超时后，在清理沙箱工作目录之前调用。
+    pub async fn abort_running(&self) {
+        for submission_id in self.tasks.abort_all().await {
+            let id = submission_id.clone();
+            let version = self
+                .db(move |store| store.last_run(&id))
+                .await
+                .ok()
+                .flatten()
+                .map(|run| run.problem_version)
+                .unwrap_or_default();
+            self.record_system_error(submission_id, version, "判题机退出，评测被中止")
+                .await;
+        }
+    }
+
+    /// 上次运行时没有评测完成的提交记录一次系统错误结果，调用方可通过重测重新评测
+    ///
+    /// 用于启动时、开始接受请求之前调用。
+    pub async fn fail_unjudged(&self) -> anyhow::Result<()> {
+        let unjudged = self.db(|store| store.unjudged_submissions()).await?;
+        if !unjudged.is_empty() {
+            warn!(count = unjudged.len(), "发现上次运行时未完成评测的提交");
+        }
+        for submission_id in unjudged {
+            self.record_system_error(submission_id, String::new(), "判题机重启，评测未完成")
+                .await;
         }
+        Ok(())
+    }
+
+    async fn record_system_error(&self, submission_id: String, version: String, message: &str) {
+        let result = JudgeResult {
+            status: JudgeStatus::SystemError,
+            time_used: Duration::ZERO,
+            memory_used: 0,
+            error_message: Some(message.to_string()),
+            test_case_results: Vec::new(),
+        };
+        let id = submission_id.clone();
+        let recorded = self
+            .db(move |store| store.record_run(&id, &version, &result))
+            .await;
+        match recorded {
+            Ok(_) => warn!(submission_id = %submission_id, "{}，已记录系统错误", message),
+            Err(e) => error!(submission_id = %submission_id, "记录系统错误结果失败: {}", e),
+        }
+    }
+
+    /// 在阻塞线程池中访问提交数据库
+    ///
+    /// SQLite 的读写是同步的，提交可能附带较大的测试数据，不能占用异步运行时的工作线程。
+    async fn db<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&SubmissionStore) -> anyhow::Result<T> + Send + 'static,
+    ) -> anyhow::Result<T> {
+        let submissions = self.submissions.clone();
+        tokio::task::spawn_blocking(move || f(&submissions)).await?
+    }
+
+    /// 在可中止的任务中处理请求，退出时被中止的请求返回 UNAVAILABLE
+    async fn tracked<T: Send + 'static>(
+        &self,
+        future: impl Future<Output = Result<T, Status>> + Send + 'static,
+    ) -> Result<T, Status> {
+        self.tasks
+            .spawn(None, future)
+            .await
+            .map_err(|_| Status::unavailable("判题机正在退出，请求已中止"))?
+    }
+
+    /// 排队运行校验程序，校验程序本身无法编译或运行时返回 INVALID_ARGUMENT
+    ///
+    /// 校验程序与评测任务一起排队，避免同时运行影响评测计时。
+    async fn validate(
+        &self,
+        validator: &ValidatorSpec,
+        test_cases: &[TestCase],
+        task: TaskInfo,
+    ) -> Result<Vec<ValidationError>, Status> {
+        let _permit = self.queue.acquire(task).await;
+        Validator::new(validator)
+            .validate_all(test_cases)
+            .await
+            .map_err(|e| {
+                error!("运行校验程序失败: {}", e);
+                Status::invalid_argument(e.to_string())
+            })
     }
 }
 
@@ -35,8 +161,761 @@ impl JudgeGrpcService for JudgeGrpcServiceImpl {
         &self,
         request: Request<SubmitRequest>,
     ) -> Result<Response<SubmitResponse>, Status> {
+        let client = request.extensions().get::<Arc<Client>>().cloned();
         let req = request.into_inner();
+        let submission_id = submission_id(&req.submission_id);
+        let span = info_span!(
+            "submission",
+            submission_id = %submission_id,
+            language = %req.language,
+            client = client.as_ref().map(|c| c.id()),
+        );
+        let service = self.clone();
+        let id = submission_id.clone();
+        let response = self
+            .tracked(
+                async move { service.judge_submission(req, &id, client).await }.instrument(span),
+            )
+            .await?;
+        Ok(Response::new(SubmitResponse {
+            submission_id,
+            ..response
+        }))
+    }
+
+    async fn run(&self, request: Request<RunRequest>) -> Result<Response<RunResponse>, Status> {
+        let client = request.extensions().get::<Arc<Client>>().cloned();
+        let req = request.into_inner();
+        let submission_id = submission_id(&req.submission_id);
+        let span = info_span!(
+            "submission",
+            submission_id = %submission_id,
+            language = %req.language,
+            client = client.as_ref().map(|c| c.id()),
+        );
+        let service = self.clone();
+        let response = self
+            .tracked(async move { service.run_custom(req, client.as_ref()).await }.instrument(span))
+            .await?;
+        Ok(Response::new(RunResponse {
+            submission_id,
+            ..response
+        }))
+    }
+
+    async fn get_queue_status(
+        &self,
+        _request: Request<GetQueueStatusRequest>,
+    ) -> Result<Response<GetQueueStatusResponse>, Status> {
+        let status = self.queue.status();
+        let convert = |tasks: Vec<QueuedTask>| -> Vec<GrpcQueuedTask> {
+            tasks
+                .into_iter()
+                .map(|queued| GrpcQueuedTask {
+                    priority: format!("{:?}", queued.task.priority).to_lowercase(),
+                    user_id: queued.task.user_id,
+                    contest_id: queued.task.contest_id,
+                    elapsed: queued.elapsed.as_millis() as f64,
+                })
+                .collect()
+        };
+
+        Ok(Response::new(GetQueueStatusResponse {
+            workers: status.workers as i32,
+            running: convert(status.running),
+            waiting: convert(status.waiting),
+        }))
+    }
+
+    async fn upload_problem(
+        &self,
+        request: Request<Streaming<UploadProblemRequest>>,
+    ) -> Result<Response<UploadProblemResponse>, Status> {
+        let service = self.clone();
+        self.tracked(async move { service.save_upload(request).await })
+            .await
+    }
+
+    async fn get_problem_version(
+        &self,
+        request: Request<GetProblemVersionRequest>,
+    ) -> Result<Response<GetProblemVersionResponse>, Status> {
+        let req = request.into_inner();
+
+        let version = self
+            .problems
+            .current_version(&req.problem_id)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+        let response = match version {
+            Some(version) => {
+                let manifest = self
+                    .problems
+                    .load(&req.problem_id, Some(&version))
+                    .map_err(|e| {
+                        error!("加载题目数据失败: {}", e);
+                        Status::data_loss(e.to_string())
+                    })?;
+                GetProblemVersionResponse {
+                    exists: true,
+                    version,
+                    test_case_count: manifest.test_cases.len() as i32,
+                }
+            }
+            None => GetProblemVersionResponse {
+                exists: false,
+                version: String::new(),
+                test_case_count: 0,
+            },
+        };
+
+        Ok(Response::new(response))
+    }
+
+    async fn import_problem(
+        &self,
+        request: Request<Streaming<ImportProblemRequest>>,
+    ) -> Result<Response<ImportProblemResponse>, Status> {
+        let service = self.clone();
+        self.tracked(async move { service.save_import(request).await })
+            .await
+    }
+
+    async fn generate_answers(
+        &self,
+        request: Request<Streaming<GenerateAnswersRequest>>,
+    ) -> Result<Response<GenerateAnswersResponse>, Status> {
+        let service = self.clone();
+        self.tracked(async move { service.save_answers(request).await })
+            .await
+    }
+    async fn list_languages(
+        &self,
+        request: Request<ListLanguagesRequest>,
+    ) -> Result<Response<ListLanguagesResponse>, Status> {
+        let service = self.clone();
+        self.tracked(async move { service.languages(request).await })
+            .await
+    }
+    async fn get_submission(
+        &self,
+        request: Request<GetSubmissionRequest>,
+    ) -> Result<Response<GetSubmissionResponse>, Status> {
+        let submission_id = request.into_inner().submission_id;
+        let id = submission_id.clone();
+        let (submission, runs) = self
+            .db(move |store| {
+                let Some(submission) = store.get(&id)? else {
+                    return Ok(None);
+                };
+                Ok(Some((submission, store.runs(&id)?)))
+            })
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+            .ok_or_else(|| Status::not_found(format!("提交 {} 不存在", submission_id)))?;
+
+        Ok(Response::new(GetSubmissionResponse {
+            submission_id,
+            language: submission.config.language,
+            problem_id: submission.problem_id,
+            user_id: submission.user_id,
+            contest_id: submission.contest_id,
+            created_at: submission.created_at as i64,
+            runs: runs
+                .into_iter()
+                .map(|run| GrpcJudgeRun {
+                    run: run.run as i32,
+                    problem_version: run.problem_version,
+                    status: run.result.status as i32,
+                    time_used: run.result.time_used.as_millis() as f64,
+                    memory_used: (run.result.memory_used as f64 / 1024.0 * 100.0).round() / 100.0,
+                    error_message: run.result.error_message.unwrap_or_default(),
+                    test_case_results: grpc_test_case_results(run.result.test_case_results),
+                    judged_at: run.judged_at as i64,
+                })
+                .collect(),
+        }))
+    }
+
+    async fn rejudge(
+        &self,
+        request: Request<RejudgeRequest>,
+    ) -> Result<Response<RejudgeResponse>, Status> {
+        let client = request.extensions().get::<Arc<Client>>().cloned();
+        let req = request.into_inner();
+        // 整批重测占用一个并发名额，直到所有提交重测完成；各提交的规模在重测前分别检查
+        let quota = Arc::new(check_quota(client.as_ref(), 0, 0).map_err(quota_status)?);
+        let submission_ids = match req.target {
+            Some(RejudgeTarget::SubmissionId(id)) => {
+                let exists = {
+                    let id = id.clone();
+                    self.db(move |store| store.get(&id))
+                        .await
+                        .map_err(|e| Status::internal(e.to_string()))?
+                        .is_some()
+                };
+                if !exists {
+                    return Err(Status::not_found(format!("提交 {} 不存在", id)));
+                }
+                vec![id]
+            }
+            Some(RejudgeTarget::ProblemId(problem_id)) => self
+                .db(move |store| store.submissions_of_problem(&problem_id))
+                .await
+                .map_err(|e| Status::internal(e.to_string()))?,
+            Some(RejudgeTarget::Submissions(list)) => {
+                if list.submission_ids.is_empty() {
+                    return Err(Status::invalid_argument("提交列表不能为空"));
+                }
+                list.submission_ids
+            }
+            None => return Err(Status::invalid_argument("必须指定提交 ID 或题目 ID")),
+        };
+        info!(count = submission_ids.len(), "开始重测");
+
+        // 各提交在独立任务中排队重测，调用方断开连接后重测仍会完成并保存结果；
+        // 同时加载的提交不超过评测并发数，整个题目的提交数据不会一起载入内存
+        let slots = Arc::new(Semaphore::new(self.queue.workers()));
+        let handles: Vec<_> = submission_ids
+            .into_iter()
+            .map(|id| {
+                let service = self.clone();
+                let version = req.problem_version.clone();
+                let client = client.clone();
+                let quota = quota.clone();
+                let slots = slots.clone();
+                let span = info_span!("rejudge", submission_id = %id);
+                self.tasks.spawn(
+                    Some(id.clone()),
+                    async move {
+                        let _quota = quota;
+                        let _slot = slots.acquire_owned().await;
+                        let outcome = service.rejudge_one(&id, &version, client.as_ref()).await;
+                        (id, outcome)
+                    }
+                    .instrument(span),
+                )
+            })
+            .collect();
+
+        let mut report = RejudgeReport::default();
+        for handle in handles {
+            let (id, outcome) = handle.await.map_err(|e| Status::internal(e.to_string()))?;
+            match outcome {
+                Ok(entry) => report.entries.push(entry),
+                Err(e) => {
+                    error!(submission_id = %id, "重测失败: {}", e);
+                    report.failures.push((id, e.to_string()));
+                }
+            }
+        }
+
+        let transitions = report.transitions();
+        let affected: Vec<String> = report
+            .affected()
+            .map(|entry| entry.submission_id.clone())
+            .collect();
+        info!(
+            rejudged = report.entries.len(),
+            affected = affected.len(),
+            failed = report.failures.len(),
+            "重测完成"
+        );
+
+        let status_code = |status: Option<JudgeStatus>| status.map_or(-1, |s| s as i32);
+        let failed = report.failures.len() as i32;
+        let mut results: Vec<RejudgeResult> = report
+            .entries
+            .into_iter()
+            .map(|entry| RejudgeResult {
+                previous_score: entry.previous_score.unwrap_or(0.0),
+                score: entry.score,
+                score_delta: entry.score_delta(),
+                submission_id: entry.submission_id,
+                run: entry.run as i32,
+                previous_status: status_code(entry.diff.previous_status),
+                status: entry.diff.status as i32,
+                changes: entry
+                    .diff
+                    .changes
+                    .into_iter()
+                    .map(|change| GrpcTestCaseChange {
+                        test_case_id: change.test_case_id as i32,
+                        previous_status: status_code(change.previous_status),
+                        status: status_code(change.status),
+                    })
+                    .collect(),
+                error_message: String::new(),
+            })
+            .collect();
+        results.extend(
+            report
+                .failures
+                .into_iter()
+                .map(|(submission_id, message)| RejudgeResult {
+                    submission_id,
+                    run: 0,
+                    previous_status: -1,
+                    status: JudgeStatus::SystemError as i32,
+                    error_message: message,
+                    ..Default::default()
+                }),
+        );
+
+        Ok(Response::new(RejudgeResponse {
+            results,
+            transitions: transitions
+                .into_iter()
+                .map(|t| GrpcVerdictTransition {
+                    previous_status: t.previous_status as i32,
+                    status: t.status as i32,
+                    count: t.count as i32,
+                })
+                .collect(),
+            affected_submission_ids: affected,
+            failed,
+        }))
+    }
+}
+
+impl JudgeGrpcServiceImpl {
+    async fn save_upload(
+        &self,
+        request: Request<Streaming<UploadProblemRequest>>,
+    ) -> Result<Response<UploadProblemResponse>, Status> {
+        let client = request.extensions().get::<Arc<Client>>().cloned();
+        let mut stream = request.into_inner();
+
+        let header = match stream.message().await? {
+            Some(UploadProblemRequest {
+                payload: Some(Payload::Header(header)),
+            }) => header,
+            _ => {
+                error!("上传的第一条消息必须是题目头信息");
+                return Err(Status::invalid_argument("第一条消息必须是题目头信息"));
+            }
+        };
+
+        if header.test_case_count <= 0 {
+            error!("测试点数量必须大于0");
+            return Err(Status::invalid_argument("测试点数量必须大于0"));
+        }
+        let limits = &settings().limits;
+        let count = header.test_case_count as usize;
+        check_problem_data(count, 0, limits).map_err(Status::invalid_argument)?;
+        // 指定了校验程序时每个测试点都要运行一次
+        let validator_time = match &header.validator {
+            Some(_) => settings().validator.time_limit_ms,
+            None => 0,
+        };
+        let _quota = check_quota(
+            client.as_ref(),
+            header.test_case_count as usize,
+            validator_time,
+        )
+        .map_err(quota_status)?;
+
+        info!(
+            problem_id = %header.problem_id,
+            version = %header.version,
+            test_case_count = %header.test_case_count,
+            "开始接收题目数据"
+        );
+
+        let mut upload = self
+            .problems
+            .begin_upload(&header.problem_id, &header.version)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        if let Some(validator) = &header.validator {
+            upload.set_config(ProblemConfig {
+                validator: Some(validator_spec(validator)),
+                ..Default::default()
+            });
+        }
+
+        let mut received = 0u64;
+        while let Some(message) = stream.message().await? {
+            let Some(Payload::Chunk(chunk)) = message.payload else {
+                return Err(Status::invalid_argument("题目头信息只能发送一次"));
+            };
+            let id =
+                check_test_case_id(chunk.test_case_id, count).map_err(Status::invalid_argument)?;
+            received += chunk.data.len() as u64;
+            check_problem_data(count, received, limits).map_err(Status::invalid_argument)?;
+            upload
+                .write_chunk(id, chunk.is_output, &chunk.data)
+                .map_err(|e| {
+                    error!("写入题目数据失败: {}", e);
+                    Status::internal(e.to_string())
+                })?;
+        }
+
+        // 校验不通过的数据不会发布，临时文件随 upload 一起清理
+        if let Some(validator) = &upload.config().validator {
+            let test_cases = upload.test_cases(header.test_case_count as usize);
+            let errors = self
+                .validate(
+                    validator,
+                    &test_cases,
+                    TaskInfo {
+                        priority: Priority::Answers,
+                        ..Default::default()
+                    },
+                )
+                .await?;
+            if !errors.is_empty() {
+                error!(
+                    problem_id = %header.problem_id,
+                    invalid_count = %errors.len(),
+                    "题目数据校验失败"
+                );
+                return Ok(Response::new(UploadProblemResponse {
+                    problem_id: header.problem_id,
+                    version: String::new(),
+                    test_case_count: header.test_case_count,
+                    validation_errors: grpc_validation_errors(errors),
+                }));
+            }
+        }
+
+        let digests: Vec<(String, String)> = header
+            .digests
+            .into_iter()
+            .map(|d| (d.input_sha256, d.output_sha256))
+            .collect();
+        let manifest = upload
+            .finish(header.test_case_count as usize, &digests)
+            .map_err(|e| {
+                error!("保存题目数据失败: {}", e);
+                publish_status(e, Status::invalid_argument)
+            })?;
+
+        info!(
+            problem_id = %manifest.problem_id,
+            version = %manifest.version,
+            "题目数据上传完成"
+        );
+
+        Ok(Response::new(UploadProblemResponse {
+            problem_id: manifest.problem_id,
+            version: manifest.version,
+            test_case_count: manifest.test_cases.len() as i32,
+            validation_errors: Vec::new(),
+        }))
+    }
+
+    async fn save_import(
+        &self,
+        request: Request<Streaming<ImportProblemRequest>>,
+    ) -> Result<Response<ImportProblemResponse>, Status> {
+        let client = request.extensions().get::<Arc<Client>>().cloned();
+        let mut stream = request.into_inner();
+
+        let header = match stream.message().await? {
+            Some(ImportProblemRequest {
+                payload: Some(ImportPayload::Header(header)),
+            }) => header,
+            _ => {
+                error!("导入的第一条消息必须是题目包头信息");
+                return Err(Status::invalid_argument("第一条消息必须是题目包头信息"));
+            }
+        };
+
+        let format: PackageFormat = header.format.parse().map_err(Status::invalid_argument)?;
+        // 测试点数在解析题目包后才知道，先占用并发名额
+        let _quota = check_quota(client.as_ref(), 0, 0).map_err(quota_status)?;
+
+        info!(
+            problem_id = %header.problem_id,
+            format = %header.format,
+            "开始接收题目包"
+        );
+
+        // 先把题目包完整写入临时目录，再按格式解析
+        let workspace = Workspace::new().map_err(|e| Status::internal(e.to_string()))?;
+        let package_path = workspace.path().join(match format {
+            PackageFormat::Fps => "package.xml",
+            _ => "package.zip",
+        });
+        let limits = &settings().limits;
+        let mut file =
+            std::fs::File::create(&package_path).map_err(|e| Status::internal(e.to_string()))?;
+        let mut received = 0u64;
+        while let Some(message) = stream.message().await? {
+            let Some(ImportPayload::Chunk(chunk)) = message.payload else {
+                return Err(Status::invalid_argument("题目包头信息只能发送一次"));
+            };
+            received += chunk.len() as u64;
+            check_problem_data(0, received, limits).map_err(Status::invalid_argument)?;
+            file.write_all(&chunk)
+                .map_err(|e| Status::internal(e.to_string()))?;
+        }
+        drop(file);
+
+        let package = import_package(format, &package_path).map_err(|e| {
+            error!("解析题目包失败: {}", e);
+            Status::invalid_argument(e.to_string())
+        })?;
+        // 压缩包解压后的大小可能远超上传的字节数，按解出的数据重新检查
+        let unpacked = package
+            .test_cases
+            .iter()
+            .map(|test_case| data_len(&test_case.input) + data_len(&test_case.expected_output))
+            .sum::<u64>();
+        check_problem_data(package.test_cases.len(), unpacked, limits)
+            .map_err(Status::invalid_argument)?;
+
+        let validator_time = match &package.config.validator {
+            Some(_) => settings().validator.time_limit_ms,
+            None => 0,
+        };
+        check_limits(client.as_ref(), package.test_cases.len(), validator_time)
+            .map_err(quota_status)?;
+
+        if let Some(validator) = &package.config.validator {
+            let errors = self
+                .validate(
+                    validator,
+                    &package.test_cases,
+                    TaskInfo {
+                        priority: Priority::Answers,
+                        ..Default::default()
+                    },
+                )
+                .await?;
+            if !errors.is_empty() {
+                error!(
+                    problem_id = %header.problem_id,
+                    invalid_count = %errors.len(),
+                    "题目数据校验失败"
+                );
+                return Ok(Response::new(ImportProblemResponse {
+                    problem_id: header.problem_id,
+                    validation_errors: grpc_validation_errors(errors),
+                    ..Default::default()
+                }));
+            }
+        }
+
+        let manifest = self
+            .problems
+            .save_package(&header.problem_id, &header.version, &package)
+            .map_err(|e| {
+                error!("导入题目包失败: {}", e);
+                publish_status(e, Status::invalid_argument)
+            })?;
+
+        info!(
+            problem_id = %manifest.problem_id,
+            version = %manifest.version,
+            test_case_count = %manifest.test_cases.len(),
+            "题目包导入完成"
+        );
 
+        Ok(Response::new(import_response(manifest)))
+    }
+
+    async fn save_answers(
+        &self,
+        request: Request<Streaming<GenerateAnswersRequest>>,
+    ) -> Result<Response<GenerateAnswersResponse>, Status> {
+        let client = request.extensions().get::<Arc<Client>>().cloned();
+        let mut stream = request.into_inner();
+
+        let header = match stream.message().await? {
+            Some(GenerateAnswersRequest {
+                payload: Some(AnswersPayload::Header(header)),
+            }) => header,
+            _ => {
+                error!("第一条消息必须是参考解头信息");
+                return Err(Status::invalid_argument("第一条消息必须是参考解头信息"));
+            }
+        };
+
+        if header.test_case_count <= 0 {
+            return Err(Status::invalid_argument("测试点数量必须大于0"));
+        }
+        if header.source_code.is_empty() {
+            return Err(Status::invalid_argument("参考解源代码不能为空"));
+        }
+        let limits = &settings().limits;
+        let count = header.test_case_count as usize;
+        let size = RequestSize {
+            time_limit_ms: header.time_limit.into(),
+            memory_limit_mb: header.memory_limit.into(),
+            source_bytes: header.source_code.len(),
+            test_cases: count,
+            input_bytes: 0,
+        };
+        size.check(limits).map_err(Status::invalid_argument)?;
+        check_problem_data(count, 0, limits).map_err(Status::invalid_argument)?;
+        let _quota =
+            check_quota(client.as_ref(), count, header.time_limit as u64).map_err(quota_status)?;
+
+        info!(
+            problem_id = %header.problem_id,
+            language = %header.language,
+            test_case_count = %header.test_case_count,
+            "开始用参考解生成答案"
+        );
+
+        let mut upload = self
+            .problems
+            .begin_upload(&header.problem_id, &header.version)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        // 沿用题目当前的 checker、子任务等配置
+        let config = self
+            .problems
+            .current_config(&header.problem_id)
+            .map_err(|e| Status::data_loss(e.to_string()))?;
+        upload.set_config(config);
+
+        let mut received = 0u64;
+        while let Some(message) = stream.message().await? {
+            let Some(AnswersPayload::Chunk(chunk)) = message.payload else {
+                return Err(Status::invalid_argument("参考解头信息只能发送一次"));
+            };
+            if chunk.is_output {
+                return Err(Status::invalid_argument("只能上传测试点输入文件"));
+            }
+            let id =
+                check_test_case_id(chunk.test_case_id, count).map_err(Status::invalid_argument)?;
+            received += chunk.data.len() as u64;
+            check_problem_data(count, received, limits).map_err(Status::invalid_argument)?;
+            upload
+                .write_chunk(id, false, &chunk.data)
+                .map_err(|e| Status::internal(e.to_string()))?;
+        }
+
+        // 先用题目的校验程序检查输入，不合法的输入不运行参考解
+        let test_cases = upload.test_cases(count);
+        if let Some(validator) = &upload.config().validator {
+            let errors = self
+                .validate(
+                    validator,
+                    &test_cases,
+                    TaskInfo {
+                        priority: Priority::Answers,
+                        ..Default::default()
+                    },
+                )
+                .await?;
+            if !errors.is_empty() {
+                error!(
+                    problem_id = %header.problem_id,
+                    invalid_count = %errors.len(),
+                    "输入数据校验失败"
+                );
+                return Ok(Response::new(GenerateAnswersResponse {
+                    problem_id: header.problem_id,
+                    test_case_count: header.test_case_count,
+                    validation_errors: grpc_validation_errors(errors),
+                    ..Default::default()
+                }));
+            }
+        }
+        let inputs: Vec<TestData> = test_cases
+            .into_iter()
+            .map(|test_case| test_case.input)
+            .collect();
+
+        // 参考解与评测任务一起排队，避免同时运行影响评测计时
+        let task = TaskInfo {
+            priority: Priority::Answers,
+            ..Default::default()
+        };
+        let permit = self.queue.acquire(task).await;
+        let reference = JudgeConfig {
+            time_limit: Duration::from_millis(header.time_limit as u64),
+            memory_limit: (header.memory_limit as u64) * 1024 * 1024,
+            language: header.language,
+            source_code: header.source_code,
+        };
+        let runs = AnswerGenerator::new(reference)
+            .generate(&inputs, |id, output| {
+                upload.write_data(id, true, &TestData::File(output.to_path_buf()))
+            })
+            .await
+            .map_err(|e| {
+                error!("运行参考解失败: {}", e);
+                Status::invalid_argument(e.to_string())
+            })?;
+        drop(permit);
+
+        let failed = runs.iter().any(|run| run.status != JudgeStatus::Accepted);
+        let results: Vec<ReferenceResult> = runs
+            .into_iter()
+            .enumerate()
+            .map(|(id, run)| ReferenceResult {
+                test_case_id: id as i32,
+                status: run.status as i32,
+                time_used: run.time_used.as_millis() as f64,
+                memory_used: (run.memory_used as f64 / 1024.0 * 100.0).round() / 100.0,
+            })
+            .collect();
+
+        // 参考解出错时不保存，临时文件随 upload 一起清理
+        let version = if failed {
+            error!(problem_id = %header.problem_id, "参考解存在超时或运行错误");
+            String::new()
+        } else {
+            let manifest = upload.finish(count, &[]).map_err(|e| {
+                error!("保存答案失败: {}", e);
+                publish_status(e, Status::internal)
+            })?;
+            info!(
+                problem_id = %manifest.problem_id,
+                version = %manifest.version,
+                "答案生成完成"
+            );
+            manifest.version
+        };
+
+        Ok(Response::new(GenerateAnswersResponse {
+            problem_id: header.problem_id,
+            version,
+            test_case_count: header.test_case_count,
+            results,
+            validation_errors: Vec::new(),
+        }))
+    }
+
+    async fn languages(
+        &self,
+        request: Request<ListLanguagesRequest>,
+    ) -> Result<Response<ListLanguagesResponse>, Status> {
+        let languages = if request.into_inner().refresh {
+            // 自检会编译运行程序，与评测任务一起排队，避免影响评测计时
+            let task = TaskInfo {
+                priority: Priority::Run,
+                ..Default::default()
+            };
+            let _permit = self.queue.acquire(task).await;
+            info!("重新执行语言自检");
+            toolchain::self_test().await
+        } else {
+            toolchain::languages()
+        };
+
+        Ok(Response::new(ListLanguagesResponse {
+            languages: languages
+                .into_iter()
+                .map(|language| LanguageInfo {
+                    name: language.name.to_string(),
+                    available: language.available,
+                    version: language.version,
+                    error: language.error.unwrap_or_default(),
+                })
+                .collect(),
+        }))
+    }
+
+    async fn judge_submission(
+        &self,
+        req: SubmitRequest,
+        submission_id: &str,
+        client: Option<Arc<Client>>,
+    ) -> Result<SubmitResponse, Status> {
         if req.language.is_empty() {
             error!("编程语言不能为空");
             return Err(Status::invalid_argument("编程语言不能为空"));
@@ -47,56 +926,207 @@ impl JudgeGrpcService for JudgeGrpcServiceImpl {
             return Err(Status::invalid_argument("源代码不能为空"));
         }
 
-        if req.time_limit <= 0 {
-            error!("时间限制必须大于0");
-            return Err(Status::invalid_argument("时间限制必须大于0"));
-        }
-
-        if req.memory_limit == 0 {
-            error!("内存限制必须大于0");
-            return Err(Status::invalid_argument("内存限制必须大于0"));
+        let size = RequestSize {
+            time_limit_ms: req.time_limit.into(),
+            memory_limit_mb: req.memory_limit.into(),
+            source_bytes: req.source_code.len(),
+            test_cases: req.test_cases.len(),
+            input_bytes: req.test_cases.iter().map(|tc| tc.input.len()).sum(),
+        };
+        if let Err(message) = size.check(&settings().limits) {
+            error!("{}", message);
+            return Err(Status::invalid_argument(message));
         }
 
-        if req.test_cases.is_empty() {
+        if req.test_cases.is_empty() && req.problem_id.is_empty() {
             error!("测试点不能为空");
             return Err(Status::invalid_argument("测试点不能为空"));
         }
 
+        if toolchain::is_disabled(&req.language) {
+            error!("语言 {} 不可用", req.language);
+            return Err(Status::failed_precondition(format!(
+                "语言 {} 不可用",
+                req.language
+            )));
+        }
+
         info!(
             language = %req.language,
             time_limit = %req.time_limit,
             memory_limit = %req.memory_limit,
             test_cases_count = %req.test_cases.len(),
+            problem_id = %req.problem_id,
+            source = %logging::source(&req.source_code),
             "收到新的提交请求"
         );
 
-        let judge_config = JudgeConfig {
-            time_limit: Duration::from_millis(req.time_limit as u64),
-            memory_limit: (req.memory_limit as u64) * 1024 * 1024, // 转换 MB 到字节
-            language: req.language,
-            source_code: req.source_code,
+        // 引用判题机上已同步的题目数据
+        let problem = if req.problem_id.is_empty() {
+            None
+        } else {
+            let manifest = self
+                .problems
+                .load(&req.problem_id, Some(&req.problem_version))
+                .map_err(|e| {
+                    error!("加载题目数据失败: {}", e);
+                    Status::failed_precondition(format!("题目数据不可用: {}", e))
+                })?;
+            Some(manifest)
         };
 
-        let mut judge = self.judge.lock().await;
-        *judge = Judge::new(judge_config);
+        // 配额在排队前检查，并发名额一直占用到评测结束
+        let test_case_count = match &problem {
+            Some(manifest) => manifest.test_cases.len(),
+            None => req.test_cases.len(),
+        };
+        let quota = check_quota(client.as_ref(), test_case_count, req.time_limit as u64)
+            .map_err(quota_status)?;
 
-        let test_cases: Vec<TestCase> = req
-            .test_cases
-            .into_iter()
-            .map(|tc: GrpcTestCase| TestCase {
-                input: tc.input,
-                expected_output: tc.expected_output,
+        let priority = match GrpcPriority::try_from(req.priority) {
+            Ok(GrpcPriority::Contest) => Priority::Contest,
+            Ok(GrpcPriority::Rejudge) => Priority::Rejudge,
+            _ => Priority::Practice,
+        };
+
+        // 评测前校验提交中附带的测试数据，与评测使用相同的优先级排队
+        if let Some(validator) = &req.validator {
+            let test_cases: Vec<TestCase> = req
+                .test_cases
+                .iter()
+                .map(|tc| TestCase {
+                    input: tc.input.clone().into(),
+                    expected_output: Vec::new().into(),
+                })
+                .collect();
+            let task = TaskInfo {
+                priority,
+                user_id: req.user_id.clone(),
+                contest_id: req.contest_id.clone(),
+            };
+            let errors = self
+                .validate(&validator_spec(validator), &test_cases, task)
+                .await?;
+            if !errors.is_empty() {
+                let details: Vec<String> = errors
+                    .iter()
+                    .map(|e| format!("#{}: {}", e.test_case_id + 1, e.message))
+                    .collect();
+                error!("测试数据校验失败: {}", details.join("; "));
+                return Err(Status::failed_precondition(format!(
+                    "测试数据校验失败: {}",
+                    details.join("; ")
+                )));
+            }
+        }
+
+        let show_output_diff = req.show_output_diff;
+        let submission = Submission {
+            id: submission_id.to_string(),
+            config: JudgeConfig {
+                time_limit: Duration::from_millis(req.time_limit as u64),
+                memory_limit: (req.memory_limit as u64) * 1024 * 1024, // 转换 MB 到字节
+                language: req.language,
+                source_code: req.source_code,
+            },
+            problem_id: req.problem_id,
+            test_cases: req
+                .test_cases
+                .into_iter()
+                .map(|tc: GrpcTestCase| TestCase {
+                    input: tc.input.into(),
+                    expected_output: tc.expected_output.into(),
+                })
+                .collect(),
+            user_id: req.user_id,
+            contest_id: req.contest_id,
+            created_at: submission::now(),
+        };
+
+        let (submission, created) = self
+            .db(move |store| {
+                let created = store.create(&submission)?;
+                Ok((submission, created))
             })
-            .collect();
+            .await
+            .map_err(|e| {
+                error!("保存提交失败: {}", e);
+                Status::internal(e.to_string())
+            })?;
+        if !created {
+            return Err(Status::already_exists(format!(
+                "提交 {} 已存在，请通过 GetSubmission 查询结果",
+                submission.id
+            )));
+        }
+
+        // 在独立任务中评测，调用方断开连接后评测仍会完成并保存结果
+        let service = self.clone();
+        let (_, result) = self
+            .tasks
+            .spawn(
+                Some(submission_id.to_string()),
+                async move {
+                    let _quota = quota;
+                    service
+                        .execute(&submission, problem.as_ref(), priority, show_output_diff)
+                        .await
+                }
+                .in_current_span(),
+            )
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+            .map_err(|e| {
+                error!("判题执行失败: {}", e);
+                Status::internal(e.to_string())
+            })?;
+
+        Ok(SubmitResponse {
+            status: result.status as i32,
+            time_used: result.time_used.as_millis() as f64,
+            memory_used: (result.memory_used as f64 / 1024.0 * 100.0).round() / 100.0,
+            error_message: result.error_message.unwrap_or_default(),
+            test_case_results: grpc_test_case_results(result.test_case_results),
+            submission_id: String::new(),
+        })
+    }
+
+    /// 排队评测一次提交并保存结果，返回评测序号和结果
+    ///
+    /// 输出差异只在本次返回给调用方，不随结果保存。
+    async fn execute(
+        &self,
+        submission: &Submission,
+        problem: Option<&ProblemManifest>,
+        priority: Priority,
+        show_output_diff: bool,
+    ) -> anyhow::Result<(u32, JudgeResult)> {
+        let task = TaskInfo {
+            priority,
+            user_id: submission.user_id.clone(),
+            contest_id: submission.contest_id.clone(),
+        };
+        let _permit = self.queue.acquire(task).await;
+
+        let judge = Judge::new(submission.config.clone()).with_output_diff(show_output_diff);
+        let judge = match problem {
+            Some(manifest) => judge.with_checker(manifest.config.checker.clone()),
+            None => judge,
+        };
+        let test_cases: Vec<TestCase> = match problem {
+            Some(manifest) => self.problems.test_cases(manifest),
+            None => submission.test_cases.clone(),
+        };
 
         info!("开始执行判题");
         let result = match judge.judge_all(&test_cases).await {
             Ok(r) => r,
             Err(e) => {
-                error!("判题执行失败: {}", e);
-                return Err(Status::internal(e.to_string()));
+                metrics().record_submission(judge.language(), "InternalError");
+                return Err(e);
             }
         };
+        metrics().record_submission(judge.language(), &format!("{:?}", result.status));
 
         info!(
             status = ?result.status,
@@ -105,26 +1135,237 @@ impl JudgeGrpcService for JudgeGrpcServiceImpl {
             "判题完成"
         );
 
-        let test_case_results: Vec<GrpcTestCaseResult> = result
-            .test_case_results
-            .into_iter()
-            .map(|tcr: TestCaseResult| GrpcTestCaseResult {
-                status: tcr.status as i32,
-                time_used: tcr.time_used.as_millis() as f64,
-                memory_used: (tcr.memory_used as f64 / 1024.0 * 100.0).round() / 100.0,
-                actual_output: tcr.actual_output,
-                test_case_id: tcr.test_case_id as i32,
-            })
-            .collect();
+        let id = submission.id.clone();
+        let version = problem.map_or(String::new(), |manifest| manifest.version.clone());
+        self.db(move |store| {
+            let run = store.record_run(&id, &version, &result)?;
+            Ok((run, result))
+        })
+        .await
+    }
+
+    /// 按最新（或指定版本的）数据重测一个提交，并与上一次结果比较
+    ///
+    /// 上一次结果按其评测时的数据版本计分，该版本已不存在时使用本次的子任务配置。
+    async fn rejudge_one(
+        &self,
+        submission_id: &str,
+        problem_version: &str,
+        client: Option<&Arc<Client>>,
+    ) -> anyhow::Result<RejudgeEntry> {
+        let id = submission_id.to_string();
+        let (submission, previous) = self
+            .db(move |store| Ok((store.get(&id)?, store.last_run(&id)?)))
+            .await?;
+        let submission =
+            submission.ok_or_else(|| anyhow::anyhow!("Submission {} not found", submission_id))?;
+        let problem = if submission.problem_id.is_empty() {
+            None
+        } else {
+            Some(
+                self.problems
+                    .load(&submission.problem_id, Some(problem_version))?,
+            )
+        };
+        let test_case_count = match &problem {
+            Some(manifest) => manifest.test_cases.len(),
+            None => submission.test_cases.len(),
+        };
+        check_limits(
+            client,
+            test_case_count,
+            submission.config.time_limit.as_millis() as u64,
+        )?;
+        let (run, result) = self
+            .execute(&submission, problem.as_ref(), Priority::Rejudge, false)
+            .await?;
+
+        let subtasks = problem
+            .as_ref()
+            .map_or(&[][..], |manifest| &manifest.config.subtasks);
+        let previous_score = previous.as_ref().map(|previous| {
+            let previous_problem = match &problem {
+                Some(manifest) if manifest.version != previous.problem_version => self
+                    .problems
+                    .load(&submission.problem_id, Some(&previous.problem_version))
+                    .ok(),
+                _ => None,
+            };
+            let previous_subtasks = previous_problem
+                .as_ref()
+                .map_or(subtasks, |manifest| &manifest.config.subtasks);
+            previous.result.score(previous_subtasks)
+        });
+
+        let diff = ResultDiff::new(previous.as_ref().map(|p| &p.result), &result);
+        let entry = RejudgeEntry {
+            submission_id: submission_id.to_string(),
+            run,
+            diff,
+            previous_score,
+            score: result.score(subtasks),
+        };
+        if entry.diff.changed() {
+            info!(
+                previous = ?entry.diff.previous_status,
+                status = ?entry.diff.status,
+                changes = entry.diff.changes.len(),
+                score_delta = entry.score_delta(),
+                "重测结果发生变化"
+            );
+        }
+        Ok(entry)
+    }
+
+    async fn run_custom(
+        &self,
+        req: RunRequest,
+        client: Option<&Arc<Client>>,
+    ) -> Result<RunResponse, Status> {
+        if req.language.is_empty() {
+            return Err(Status::invalid_argument("编程语言不能为空"));
+        }
+        if req.source_code.is_empty() {
+            return Err(Status::invalid_argument("源代码不能为空"));
+        }
+        let size = RequestSize {
+            time_limit_ms: req.time_limit.into(),
+            memory_limit_mb: req.memory_limit.into(),
+            source_bytes: req.source_code.len(),
+            test_cases: 0,
+            input_bytes: req.stdin.len(),
+        };
+        size.check(&settings().limits)
+            .map_err(Status::invalid_argument)?;
+        if toolchain::is_disabled(&req.language) {
+            return Err(Status::failed_precondition(format!(
+                "语言 {} 不可用",
+                req.language
+            )));
+        }
+
+        info!(
+            language = %req.language,
+            time_limit = %req.time_limit,
+            memory_limit = %req.memory_limit,
+            stdin_size = %req.stdin.len(),
+            source = %logging::source(&req.source_code),
+            "收到自定义输入运行请求"
+        );
+
+        let judge = Judge::new(JudgeConfig {
+            time_limit: Duration::from_millis(req.time_limit as u64),
+            memory_limit: (req.memory_limit as u64) * 1024 * 1024,
+            language: req.language,
+            source_code: req.source_code,
+        });
 
-        let response = SubmitResponse {
+        let _quota = check_quota(client, 1, req.time_limit as u64).map_err(quota_status)?;
+
+        let task = TaskInfo {
+            priority: Priority::Run,
+            user_id: req.user_id,
+            contest_id: String::new(),
+        };
+        let _permit = self.queue.acquire(task).await;
+        let result = judge.run(&req.stdin.into()).await.map_err(|e| {
+            error!("运行失败: {}", e);
+            Status::internal(e.to_string())
+        })?;
+
+        info!(status = ?result.status, time_used = ?result.time_used, "运行完成");
+
+        Ok(RunResponse {
             status: result.status as i32,
             time_used: result.time_used.as_millis() as f64,
             memory_used: (result.memory_used as f64 / 1024.0 * 100.0).round() / 100.0,
+            exit_code: result.exit_code.unwrap_or(-1),
+            stdout: result.stdout,
+            stderr: result.stderr,
             error_message: result.error_message.unwrap_or_default(),
-            test_case_results,
-        };
+            submission_id: String::new(),
+        })
+    }
+}
 
-        Ok(Response::new(response))
+fn grpc_test_case_results(results: Vec<TestCaseResult>) -> Vec<GrpcTestCaseResult> {
+    results
+        .into_iter()
+        .map(|tcr| GrpcTestCaseResult {
+            status: tcr.status as i32,
+            time_used: tcr.time_used.as_millis() as f64,
+            memory_used: (tcr.memory_used as f64 / 1024.0 * 100.0).round() / 100.0,
+            actual_output: tcr.actual_output,
+            test_case_id: tcr.test_case_id as i32,
+            output_diff: tcr.output_diff.map(|diff| GrpcOutputDiff {
+                line: diff.line as i32,
+                column: diff.column as i32,
+                unified_diff: diff.unified_diff,
+                truncated: diff.truncated,
+            }),
+        })
+        .collect()
+}
+
+/// 调用方未提供提交 ID 时生成一个
+fn submission_id(requested: &str) -> String {
+    if requested.is_empty() {
+        logging::submission_id()
+    } else {
+        requested.to_string()
+    }
+}
+
+fn import_response(manifest: ProblemManifest) -> ImportProblemResponse {
+    let config = manifest.config;
+    let checker = match config.checker {
+        CheckerSpec::Exact => "exact",
+        CheckerSpec::Tokens => "tokens",
+        CheckerSpec::Float { .. } => "float",
+        CheckerSpec::Custom { .. } => "custom",
+    };
+    ImportProblemResponse {
+        problem_id: manifest.problem_id,
+        version: manifest.version,
+        name: config.name,
+        test_case_count: manifest.test_cases.len() as i32,
+        subtask_count: config.subtasks.len() as i32,
+        time_limit: config.time_limit_ms.unwrap_or(0) as i32,
+        memory_limit: config.memory_limit_mb.unwrap_or(0) as i32,
+        checker: checker.to_string(),
+        validation_errors: Vec::new(),
+    }
+}
+
+fn validator_spec(validator: &GrpcValidatorProgram) -> ValidatorSpec {
+    ValidatorSpec {
+        language: validator.language.clone(),
+        source_code: validator.source_code.clone(),
+    }
+}
+
+/// 发布题目数据失败时的状态码，版本已存在且内容不同时返回 ALREADY_EXISTS
+fn publish_status(error: anyhow::Error, fallback: fn(String) -> Status) -> Status {
+    match error.downcast_ref::<VersionConflict>() {
+        Some(conflict) => Status::already_exists(conflict.to_string()),
+        None => fallback(error.to_string()),
+    }
+}
+
+fn grpc_validation_errors(errors: Vec<ValidationError>) -> Vec<GrpcValidationError> {
+    errors
+        .into_iter()
+        .map(|e| GrpcValidationError {
+            test_case_id: e.test_case_id as i32,
+            message: e.message,
+        })
+        .collect()
+}
+
+/// 测试数据的字节数，文件读取不到元数据时按 0 计
+fn data_len(data: &TestData) -> u64 {
+    match data {
+        TestData::Inline(data) => data.len() as u64,
+        TestData::File(path) => std::fs::metadata(path).map_or(0, |m| m.len()),
     }
 }
@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+use tokio::task::{AbortHandle, JoinHandle};
+
+/// 正在执行、可能占用沙箱的任务
+///
+/// 退出时等待超时后中止这些任务并等待它们结束，任务中的子进程随之被杀死，
+/// 之后才能安全地清理沙箱工作目录。
+#[derive(Clone, Default)]
+pub struct RunningTasks {
+    state: Arc<Mutex<State>>,
+    idle: Arc<Notify>,
+}
+
+#[derive(Default)]
+struct State {
+    next_id: u64,
+    tasks: HashMap<u64, Entry>,
+    closed: bool, // 已开始中止，之后启动的任务立即中止
+}
+
+struct Entry {
+    submission_id: Option<String>, // 评测中的提交，中止时据此记录结果
+    abort: AbortHandle,
+}
+
+impl RunningTasks {
+    /// 在独立任务中执行 `future`，`submission_id` 为该任务评测的提交
+    pub fn spawn<F>(&self, submission_id: Option<String>, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        // 持有锁直到登记完成，任务结束时的注销不会早于登记
+        let mut state = self.state.lock().unwrap();
+        let id = state.next_id;
+        state.next_id += 1;
+        let guard = TaskGuard {
+            tasks: self.clone(),
+            id,
+        };
+        let handle = tokio::spawn(async move {
+            let _guard = guard;
+            future.await
+        });
+        if state.closed {
+            handle.abort();
+        }
+        state.tasks.insert(
+            id,
+            Entry {
+                submission_id,
+                abort: handle.abort_handle(),
+            },
+        );
+        handle
+    }
+
+    /// 中止所有任务并等待它们结束，返回被中止的提交 ID
+    pub async fn abort_all(&self) -> Vec<String> {
+        let submission_ids = {
+            let mut state = self.state.lock().unwrap();
+            state.closed = true;
+            state
+                .tasks
+                .values()
+                .filter_map(|entry| {
+                    entry.abort.abort();
+                    entry.submission_id.clone()
+                })
+                .collect()
+        };
+        loop {
+            // 先注册通知再检查，避免错过最后一个任务结束时的通知
+            let idle = self.idle.notified();
+            if self.state.lock().unwrap().tasks.is_empty() {
+                break;
+            }
+            idle.await;
+        }
+        submission_ids
+    }
+}
+
+/// 任务结束或被中止时注销
+struct TaskGuard {
+    tasks: RunningTasks,
+    id: u64,
+}
+
+impl Drop for TaskGuard {
+    fn drop(&mut self) {
+        let mut state = self.tasks.state.lock().unwrap();
+        state.tasks.remove(&self.id);
+        if state.tasks.is_empty() {
+            self.tasks.idle.notify_waiters();
+        }
+    }
+}
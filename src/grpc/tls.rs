@@ -0,0 +1,29 @@
+use anyhow::Result;
+use opti_neko::config::TlsSettings;
+use std::fs;
+use std::path::Path;
+use tonic::transport::{Certificate, Identity, ServerTlsConfig};
+
+/// 按配置构造 TLS 参数，未启用时返回 `None`
+pub fn server_config(tls: &TlsSettings) -> Result<Option<ServerTlsConfig>> {
+    if !tls.enabled {
+        return Ok(None);
+    }
+
+    let cert = read(&tls.cert_path, "certificate")?;
+    let key = read(&tls.key_path, "private key")?;
+    let mut config = ServerTlsConfig::new().identity(Identity::from_pem(cert, key));
+
+    if let Some(path) = &tls.client_ca_path {
+        // 配置了客户端 CA 后，未出示有效证书的连接在握手阶段即被拒绝
+        let ca = read(path, "client CA certificate")?;
+        config = config.client_ca_root(Certificate::from_pem(ca));
+    }
+
+    Ok(Some(config))
+}
+
+fn read(path: &Path, what: &str) -> Result<Vec<u8>> {
+    fs::read(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read TLS {} {}: {}", what, path.display(), e))
+}
@@ -0,0 +1,66 @@
+use crate::judge::runner::Runner;
+use crate::judge::types::{JudgeConfig, JudgeStatus, TestData};
+use anyhow::Result;
+use std::path::Path;
+use std::time::Duration;
+use tracing::info;
+
+/// 用参考解生成答案文件
+pub struct AnswerGenerator {
+    reference: Runner,
+}
+
+/// 参考解在单个输入上的运行结果
+#[derive(Debug, Clone)]
+pub struct ReferenceRun {
+    pub status: JudgeStatus, // Accepted 表示正常结束，否则为 TimeLimitExceeded 或 RuntimeError
+    pub time_used: Duration,
+    pub memory_used: u64,
+}
+
+impl AnswerGenerator {
+    pub fn new(reference: JudgeConfig) -> Self {
+        Self {
+            reference: Runner::new(reference),
+        }
+    }
+
+    /// 在每个输入上运行参考解
+    ///
+    /// 正常结束时以测试点下标和输出文件路径调用 `on_output`，输出文件在下一次运行前有效。
+    /// 超时或运行错误的测试点只记录在返回结果中，调用方据此决定是否保存。
+    pub async fn generate(
+        &self,
+        inputs: &[TestData],
+        mut on_output: impl FnMut(usize, &Path) -> Result<()>,
+    ) -> Result<Vec<ReferenceRun>> {
+        self.reference
+            .compile()
+            .await
+            .map_err(|e| anyhow::anyhow!("Reference {}", e))?;
+
+        let mut runs = Vec::with_capacity(inputs.len());
+        for (id, input) in inputs.iter().enumerate() {
+            let output = self.reference.run_with_args(input, &[]).await?;
+            let status = if output.timed_out {
+                JudgeStatus::TimeLimitExceeded
+            } else if !output.success() {
+                JudgeStatus::RuntimeError
+            } else {
+                on_output(id, &output.output_path)?;
+                JudgeStatus::Accepted
+            };
+
+            if status != JudgeStatus::Accepted {
+                info!("参考解在测试点 #{} 上结果为 {:?}", id + 1, status);
+            }
+            runs.push(ReferenceRun {
+                status,
+                time_used: output.time_used,
+                memory_used: output.memory_used,
+            });
+        }
+
+        Ok(runs)
+    }
+}
@@ -0,0 +1,197 @@
+use crate::config::settings;
+use crate::judge::language::{Language, LanguageFamily};
+use anyhow::Result;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+use tracing::warn;
+
+/// 记录最近使用时间的标记文件
+const STAMP_FILE: &str = ".last_used";
+
+static TMP_ID: AtomicU64 = AtomicU64::new(0);
+static COMPILER_VERSIONS: OnceLock<Mutex<HashMap<String, String>>> = OnceLock::new();
+
+/// 编译产物缓存
+///
+/// 以 (语言变体, 编译器版本, 编译参数, 源代码) 的哈希为键，每个条目是缓存目录下的
+/// 一个子目录，保存编译完成后工作目录中除源文件外的所有文件。总大小超过上限时
+/// 按最近使用时间淘汰。选手程序、checker、interactor 都通过 `Runner::compile`
+/// 编译，因此共用同一份缓存。
+pub struct ArtifactCache {
+    dir: PathBuf,
+    max_size: u64,
+}
+
+impl ArtifactCache {
+    pub fn new(dir: impl Into<PathBuf>, max_size: u64) -> Self {
+        Self {
+            dir: dir.into(),
+            max_size,
+        }
+    }
+
+    /// 按全局配置创建缓存，未启用时返回 `None`
+    pub fn from_settings() -> Option<Self> {
+        let cache = &settings().cache;
+        cache
+            .enabled
+            .then(|| Self::new(&cache.dir, cache.max_size_mb * 1024 * 1024))
+    }
+
+    /// `sources` 为源代码及参与编译的附加文件内容
+    pub fn key(language: &Language, compile_command: &[String], sources: &[Vec<u8>]) -> String {
+        let mut hasher = Sha256::new();
+        for part in [
+            language.name,
+            &compiler_version(language, compile_command.first().map_or("", String::as_str)),
+            &compile_command.join("\0"),
+        ] {
+            hasher.update(part.as_bytes());
+            hasher.update([0u8]);
+        }
+        for source in sources {
+            hasher.update((source.len() as u64).to_le_bytes());
+            hasher.update(source);
+        }
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// 命中时把缓存的产物复制到 `dest`，并刷新最近使用时间
+    pub fn restore(&self, key: &str, dest: &Path) -> Result<bool> {
+        let entry = self.dir.join(key);
+        if !entry.is_dir() {
+            return Ok(false);
+        }
+
+        for file in fs::read_dir(&entry)? {
+            let file = file?;
+            if file.file_name() != STAMP_FILE && file.file_type()?.is_file() {
+                fs::copy(file.path(), dest.join(file.file_name()))?;
+            }
+        }
+        touch(&entry.join(STAMP_FILE))?;
+
+        Ok(true)
+    }
+
+    /// 保存 `src` 目录中除 `exclude` 外的所有文件，然后按需淘汰旧条目
+    pub fn store(&self, key: &str, src: &Path, exclude: &[String]) -> Result<()> {
+        let entry = self.dir.join(key);
+        if entry.is_dir() {
+            return Ok(());
+        }
+
+        // 先写入临时目录再重命名，避免并发编译读到不完整的条目
+        let tmp = self.dir.join(format!(
+            ".tmp-{}-{}",
+            std::process::id(),
+            TMP_ID.fetch_add(1, Ordering::SeqCst)
+        ));
+        fs::create_dir_all(&tmp)?;
+        for file in fs::read_dir(src)? {
+            let file = file?;
+            let excluded = exclude.iter().any(|name| file.file_name() == name.as_str());
+            if !excluded && file.file_type()?.is_file() {
+                fs::copy(file.path(), tmp.join(file.file_name()))?;
+            }
+        }
+        touch(&tmp.join(STAMP_FILE))?;
+
+        if fs::rename(&tmp, &entry).is_err() {
+            fs::remove_dir_all(&tmp).ok();
+        }
+
+        self.evict()
+    }
+
+    /// 淘汰最久未使用的条目，直到总大小不超过上限
+    pub fn evict(&self) -> Result<()> {
+        let mut entries = Vec::new();
+        let mut total = 0;
+
+        for entry in fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir() || entry.file_name().to_string_lossy().starts_with('.')
+            {
+                continue;
+            }
+
+            let mut size = 0;
+            for file in fs::read_dir(entry.path())? {
+                size += file?.metadata()?.len();
+            }
+            let last_used = fs::metadata(entry.path().join(STAMP_FILE))
+                .and_then(|m| m.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+
+            total += size;
+            entries.push((last_used, size, entry.path()));
+        }
+
+        entries.sort_by_key(|(last_used, _, _)| *last_used);
+        for (_, size, path) in entries {
+            if total <= self.max_size {
+                break;
+            }
+            fs::remove_dir_all(&path)?;
+            total -= size;
+        }
+
+        Ok(())
+    }
+}
+
+fn touch(path: &Path) -> Result<()> {
+    let file = File::options().create(true).append(true).open(path)?;
+    file.set_modified(SystemTime::now())?;
+    Ok(())
+}
+
+/// 清空已查询的编译器版本，工具链升级后重新自检时调用，之后的编译缓存键随新版本变化
+pub(crate) fn forget_compiler_versions() {
+    if let Some(versions) = COMPILER_VERSIONS.get() {
+        versions.lock().unwrap().clear();
+    }
+}
+
+/// 查询编译器或解释器版本，每个程序只查询一次
+pub(crate) fn compiler_version(language: &Language, program: &str) -> String {
+    let program = program.to_string();
+    let versions = COMPILER_VERSIONS.get_or_init(Default::default);
+
+    if let Some(version) = versions.lock().unwrap().get(&program) {
+        return version.clone();
+    }
+
+    let version_arg = match language.family {
+        LanguageFamily::Go => "version",
+        _ => "--version",
+    };
+    let version = match Command::new(&program).arg(version_arg).output() {
+        Ok(output) => {
+            let text = if output.stdout.is_empty() {
+                output.stderr
+            } else {
+                output.stdout
+            };
+            String::from_utf8_lossy(&text)
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string()
+        }
+        Err(e) => {
+            warn!("获取编译器 {} 版本失败: {}", program, e);
+            String::new()
+        }
+    };
+
+    versions.lock().unwrap().insert(program, version.clone());
+    version
+}
@@ -1,20 +1,230 @@
-use crate::judge::types::{JudgeStatus, TestCase};
+use crate::config::settings;
+use crate::judge::runner::Runner;
+use crate::judge::types::{
+    CheckerSpec, CheckerStyle, JudgeConfig, JudgeStatus, TestCase, TestData,
+};
+use anyhow::Result;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Bytes};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-pub struct Checker;
+pub struct Checker {
+    spec: CheckerSpec,
+    program: Option<Runner>, // 特殊评测程序
+}
 
 impl Checker {
-    pub fn new() -> Self {
-        Self
+    pub fn new(spec: CheckerSpec) -> Self {
+        let program = match &spec {
+            CheckerSpec::Custom {
+                language,
+                source_code,
+                ..
+            } => {
+                let checker = &settings().checker;
+                let config = JudgeConfig {
+                    time_limit: Duration::from_millis(checker.time_limit_ms),
+                    memory_limit: checker.memory_limit_mb * 1024 * 1024,
+                    language: language.clone(),
+                    source_code: source_code.clone(),
+                };
+                Some(Runner::new(config).with_files(testlib_files()))
+            }
+            _ => None,
+        };
+        Self { spec, program }
+    }
+
+    /// 编译特殊评测程序，内置比较方式无需准备
+    pub async fn prepare(&self) -> Result<()> {
+        if let Some(program) = &self.program {
+            program
+                .compile()
+                .await
+                .map_err(|e| anyhow::anyhow!("Checker {}", e))?;
+        }
+        Ok(())
     }
 
-    pub fn check(&self, test_case: &TestCase, actual_output: &str) -> JudgeStatus {
-        let expected = test_case.expected_output.trim();
-        let actual = actual_output.trim();
+    pub async fn check(&self, test_case: &TestCase, actual_output: &Path) -> Result<JudgeStatus> {
+        let accepted = match &self.spec {
+            CheckerSpec::Exact => {
+                let expected = test_case.expected_output.reader()?;
+                let actual = BufReader::new(File::open(actual_output)?);
+                compare_exact(expected, actual)?
+            }
+            CheckerSpec::Tokens => {
+                let expected = test_case.expected_output.reader()?;
+                let actual = BufReader::new(File::open(actual_output)?);
+                compare_tokens(expected, actual, |a, b| a == b)?
+            }
+            CheckerSpec::Float { precision } => {
+                let expected = test_case.expected_output.reader()?;
+                let actual = BufReader::new(File::open(actual_output)?);
+                compare_tokens(expected, actual, |a, b| float_equal(a, b, *precision))?
+            }
+            CheckerSpec::Custom { style, .. } => {
+                return self.check_custom(test_case, actual_output, *style).await;
+            }
+        };
 
-        if expected == actual {
+        Ok(if accepted {
             JudgeStatus::Accepted
         } else {
             JudgeStatus::WrongAnswer
+        })
+    }
+
+    async fn check_custom(
+        &self,
+        test_case: &TestCase,
+        actual_output: &Path,
+        style: CheckerStyle,
+    ) -> Result<JudgeStatus> {
+        let program = self
+            .program
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Checker is not prepared"))?;
+
+        let input = program.materialize("input.txt", &test_case.input)?;
+        let answer = program.materialize("answer.txt", &test_case.expected_output)?;
+        let output = actual_output.to_path_buf();
+        let args: Vec<String> = match style {
+            CheckerStyle::Testlib => [input, output, answer],
+            CheckerStyle::Hustoj => [input, answer, output],
+        }
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect();
+
+        let result = program
+            .run_with_args(&TestData::Inline(Vec::new()), &args)
+            .await?;
+
+        // testlib 约定：0 正确，1 答案错误，2 格式错误，其余为评测程序自身出错
+        match result.exit_code {
+            _ if result.timed_out => Err(anyhow::anyhow!("Checker time limit exceeded")),
+            Some(0) => Ok(JudgeStatus::Accepted),
+            Some(1) | Some(2) => Ok(JudgeStatus::WrongAnswer),
+            code => Err(anyhow::anyhow!("Checker failed with exit code {:?}", code)),
+        }
+    }
+}
+
+/// 编译 testlib 风格的辅助程序（checker、validator）时复制到工作目录的头文件
+pub fn testlib_files() -> Vec<PathBuf> {
+    settings()
+        .checker
+        .testlib_path
+        .iter()
+        .filter(|path| path.exists())
+        .cloned()
+        .collect()
+}
+
+impl Default for Checker {
+    fn default() -> Self {
+        Self::new(CheckerSpec::Exact)
+    }
+}
+
+/// 忽略首尾空白逐字节比较
+///
+/// 跳过开头的空白后逐字节比较，到第一处不同或一方结束时，双方剩余的内容都只有空白才算相同，
+/// 中间的空白不需要缓存。
+fn compare_exact(expected: impl BufRead, actual: impl BufRead) -> io::Result<bool> {
+    let mut expected = skip_leading_whitespace(expected);
+    let mut actual = skip_leading_whitespace(actual);
+
+    loop {
+        match (expected.next().transpose()?, actual.next().transpose()?) {
+            (None, None) => return Ok(true),
+            (Some(a), Some(b)) if a == b => continue,
+            (a, b) => {
+                let blank = |byte: Option<u8>| byte.is_none_or(|b| b.is_ascii_whitespace());
+                return Ok(blank(a)
+                    && blank(b)
+                    && rest_is_blank(&mut expected)?
+                    && rest_is_blank(&mut actual)?);
+            }
+        }
+    }
+}
+
+fn skip_leading_whitespace(reader: impl BufRead) -> impl Iterator<Item = io::Result<u8>> {
+    reader
+        .bytes()
+        .skip_while(|byte| matches!(byte, Ok(b) if b.is_ascii_whitespace()))
+}
+
+fn rest_is_blank(bytes: &mut impl Iterator<Item = io::Result<u8>>) -> io::Result<bool> {
+    for byte in bytes {
+        if !byte?.is_ascii_whitespace() {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+fn compare_tokens(
+    expected: impl BufRead,
+    actual: impl BufRead,
+    equal: impl Fn(&[u8], &[u8]) -> bool,
+) -> io::Result<bool> {
+    let mut expected = Tokens::new(expected);
+    let mut actual = Tokens::new(actual);
+
+    loop {
+        match (expected.next().transpose()?, actual.next().transpose()?) {
+            (None, None) => return Ok(true),
+            (Some(a), Some(b)) if equal(&a, &b) => continue,
+            _ => return Ok(false),
+        }
+    }
+}
+
+/// 两个单词都是数字时按误差比较，否则要求完全相同
+fn float_equal(expected: &[u8], actual: &[u8], precision: f64) -> bool {
+    let parse = |token: &[u8]| std::str::from_utf8(token).ok()?.parse::<f64>().ok();
+    match (parse(expected), parse(actual)) {
+        (Some(e), Some(a)) if e.is_finite() && a.is_finite() => {
+            let diff = (e - a).abs();
+            diff <= precision + 1e-15 || diff <= precision * e.abs() + 1e-15
+        }
+        _ => expected == actual,
+    }
+}
+
+/// 以空白分隔的单词流
+struct Tokens<R: BufRead> {
+    bytes: Bytes<R>,
+}
+
+impl<R: BufRead> Tokens<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            bytes: reader.bytes(),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for Tokens<R> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut token = Vec::new();
+        for byte in self.bytes.by_ref() {
+            match byte {
+                Err(e) => return Some(Err(e)),
+                Ok(byte) if byte.is_ascii_whitespace() => {
+                    if !token.is_empty() {
+                        return Some(Ok(token));
+                    }
+                }
+                Ok(byte) => token.push(byte),
+            }
         }
+        (!token.is_empty()).then_some(Ok(token))
     }
 }
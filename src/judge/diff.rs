@@ -0,0 +1,355 @@
+use crate::config::settings;
+use crate::judge::types::{OutputDiff, TestCase};
+use anyhow::Result;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::fs::File;
+use std::hash::Hasher;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+/// 第一处不一致之后参与比较的最大行数，超出部分不再展示
+const WINDOW_LINES: usize = 32;
+/// 单行在差异中展示的最大字符数
+const MAX_LINE_CHARS: usize = 200;
+/// 单行保留在内存中的最大字节数，足够容纳 `MAX_LINE_CHARS` 个 UTF-8 字符
+const MAX_LINE_BYTES: usize = MAX_LINE_CHARS * 4;
+
+/// 比较期望输出和实际输出，返回第一处不一致的位置和附近的统一差异格式片段
+///
+/// 按行逐字节比较，行尾的空白也参与比较，只忽略整个输出末尾的空白和空行。
+/// `Exact` 比较方式还忽略整个输出开头的空白，这里不做处理，行号与原始输出一致。
+/// 除末尾空白外完全相同（如由特殊评测程序判为错误）时返回 `None`。
+pub fn output_diff(test_case: &TestCase, actual_output: &Path) -> Result<Option<OutputDiff>> {
+    let expected = test_case.expected_output.reader()?;
+    let actual = BufReader::new(File::open(actual_output)?);
+    let judge = &settings().judge;
+    Ok(diff(
+        expected,
+        actual,
+        judge.diff_context_lines,
+        judge.diff_max_bytes,
+    )?)
+}
+
+/// 逐行比较两个输出，`context` 为不一致处前后保留的相同行数，差异文本不超过 `max_bytes`
+pub fn diff(
+    expected: impl BufRead,
+    actual: impl BufRead,
+    context: usize,
+    max_bytes: usize,
+) -> io::Result<Option<OutputDiff>> {
+    let mut expected = Lines::new(expected);
+    let mut actual = Lines::new(actual);
+
+    // 逐行跳过相同的部分，保留最近的几行作为上文
+    let mut before: VecDeque<Line> = VecDeque::new();
+    let mut line = 1;
+    let (first_expected, first_actual) = loop {
+        let e = expected.next()?;
+        let a = actual.next()?;
+        if e == a {
+            let Some(same) = e else {
+                return Ok(None);
+            };
+            before.push_back(same);
+            if before.len() > context {
+                before.pop_front();
+            }
+            line += 1;
+            continue;
+        }
+        // 只有整个输出末尾的空白不同时视为相同
+        let trimmed = |line: &Option<Line>| line.clone().unwrap_or_default().trim_end();
+        if trimmed(&e) == trimmed(&a) && expected.rest_is_blank()? && actual.rest_is_blank()? {
+            return Ok(None);
+        }
+        break (e, a);
+    };
+
+    // 超长的行只比较保留的前缀，前缀相同时列号指向前缀之后
+    let column = match (&first_expected, &first_actual) {
+        (Some(e), Some(a)) => {
+            e.text
+                .chars()
+                .zip(a.text.chars())
+                .take_while(|(x, y)| x == y)
+                .count()
+                + 1
+        }
+        _ => 1,
+    };
+
+    let mut old: Vec<Line> = first_expected.into_iter().collect();
+    let mut new: Vec<Line> = first_actual.into_iter().collect();
+    let mut truncated = expected.fill(&mut old)?;
+    truncated |= actual.fill(&mut new)?;
+
+    let mut ops = edit_script(&old, &new);
+    // 不一致之后只保留 `context` 行相同的下文
+    if let Some(last) = ops.iter().rposition(|op| !matches!(op, Op::Same(_))) {
+        ops.truncate(last + 1 + context);
+    }
+
+    let start = line - before.len();
+    let old_len = before.len() + ops.iter().filter(|op| !matches!(op, Op::Added(_))).count();
+    let new_len = before.len()
+        + ops
+            .iter()
+            .filter(|op| !matches!(op, Op::Removed(_)))
+            .count();
+    let mut text = format!(
+        "--- expected\n+++ actual\n@@ -{} +{} @@\n",
+        range(start, old_len),
+        range(start, new_len)
+    );
+    let lines = before
+        .iter()
+        .map(|l| (' ', l))
+        .chain(ops.iter().map(|op| match op {
+            Op::Same(l) => (' ', *l),
+            Op::Removed(l) => ('-', *l),
+            Op::Added(l) => ('+', *l),
+        }));
+    for (prefix, content) in lines {
+        let mut entry = String::new();
+        entry.push(prefix);
+        entry.extend(content.text.chars().take(MAX_LINE_CHARS));
+        if content.rest.is_some() || content.text.chars().count() > MAX_LINE_CHARS {
+            entry.push_str("...");
+        }
+        entry.push('\n');
+        if text.len() + entry.len() > max_bytes {
+            truncated = true;
+            break;
+        }
+        text.push_str(&entry);
+    }
+    if truncated {
+        writeln!(text, "... (diff truncated)").ok();
+    }
+
+    Ok(Some(OutputDiff {
+        line,
+        column,
+        unified_diff: text,
+        truncated,
+    }))
+}
+
+/// 统一差异格式的行范围，行数为 0 时起始行为前一行
+fn range(start: usize, len: usize) -> String {
+    match len {
+        0 => format!("{},0", start - 1),
+        1 => start.to_string(),
+        _ => format!("{},{}", start, len),
+    }
+}
+
+enum Op<'a> {
+    Same(&'a Line),
+    Removed(&'a Line),
+    Added(&'a Line),
+}
+
+/// 基于最长公共子序列的逐行编辑脚本，输入行数不超过 `WINDOW_LINES`
+fn edit_script<'a>(old: &'a [Line], new: &'a [Line]) -> Vec<Op<'a>> {
+    let (n, m) = (old.len(), new.len());
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && old[i] == new[j] {
+            ops.push(Op::Same(&old[i]));
+            i += 1;
+            j += 1;
+        } else if j < m && (i == n || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            ops.push(Op::Added(&new[j]));
+            j += 1;
+        } else {
+            ops.push(Op::Removed(&old[i]));
+            i += 1;
+        }
+    }
+
+    // 同一处修改先列出删除的行，再列出新增的行
+    let mut ordered = Vec::with_capacity(ops.len());
+    let mut added = Vec::new();
+    for op in ops {
+        match op {
+            Op::Added(_) => added.push(op),
+            Op::Removed(_) => ordered.push(op),
+            Op::Same(_) => {
+                ordered.append(&mut added);
+                ordered.push(op);
+            }
+        }
+    }
+    ordered.append(&mut added);
+    ordered
+}
+
+/// 不含换行符的一行，非 UTF-8 字节按替换字符处理
+///
+/// 只保留开头的 `MAX_LINE_BYTES` 字节，超出的部分以摘要参与比较，超长的行不会整体载入内存。
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Line {
+    text: String,
+    rest: Option<u64>,    // 超出保留长度部分的摘要，行不超长时为 None
+    trimmed: Option<u64>, // 去掉行尾空白后超出保留长度部分的摘要
+}
+
+impl Line {
+    /// 只包含空白
+    fn is_blank(&self) -> bool {
+        self.trimmed.is_none() && self.text.trim_end_matches(is_space).is_empty()
+    }
+
+    /// 去掉行尾空白，用于整个输出的最后一行
+    fn trim_end(mut self) -> Line {
+        match self.trimmed {
+            Some(_) => self.rest = self.trimmed,
+            None => {
+                self.text
+                    .truncate(self.text.trim_end_matches(is_space).len());
+                self.rest = None;
+            }
+        }
+        self
+    }
+}
+
+fn is_space(c: char) -> bool {
+    c.is_ascii_whitespace()
+}
+
+/// 逐行读取输出
+struct Lines<R: BufRead> {
+    reader: R,
+    ahead: VecDeque<Line>, // 向前查看时读到的空白行，之后依次返回
+    skipped: usize,        // 超出 `WINDOW_LINES` 而未保留的空白行数，它们不会出现在差异中
+    pending: Option<Line>, // 向前查看时读到的第一个非空白行
+}
+
+impl<R: BufRead> Lines<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            ahead: VecDeque::new(),
+            skipped: 0,
+            pending: None,
+        }
+    }
+
+    fn next(&mut self) -> io::Result<Option<Line>> {
+        if let Some(line) = self.ahead.pop_front() {
+            return Ok(Some(line));
+        }
+        if self.skipped > 0 {
+            self.skipped -= 1;
+            return Ok(Some(Line::default()));
+        }
+        if let Some(line) = self.pending.take() {
+            return Ok(Some(line));
+        }
+        self.read()
+    }
+
+    fn read(&mut self) -> io::Result<Option<Line>> {
+        let mut kept = Vec::new();
+        let mut len = 0; // 去掉行尾空白后的长度
+        let mut total = 0;
+        // 超出保留长度的部分计入摘要，`committed` 不含当前读到的行尾空白，`tentative` 包含
+        let mut committed = DefaultHasher::new();
+        let mut tentative = committed.clone();
+        loop {
+            let buf = self.reader.fill_buf()?;
+            if buf.is_empty() {
+                if total == 0 {
+                    return Ok(None);
+                }
+                break;
+            }
+            let (chunk, consumed, done) = match buf.iter().position(|&b| b == b'\n') {
+                Some(i) => (&buf[..i], i + 1, true),
+                None => (buf, buf.len(), false),
+            };
+
+            let keep = chunk.len().min(MAX_LINE_BYTES.saturating_sub(total));
+            kept.extend_from_slice(&chunk[..keep]);
+            if let Some(last) = chunk.iter().rposition(|b| !b.is_ascii_whitespace()) {
+                len = total + last + 1;
+                if last >= keep {
+                    tentative.write(&chunk[keep..=last]);
+                    committed = tentative.clone();
+                }
+            }
+            tentative.write(&chunk[keep.max(len.saturating_sub(total))..]);
+            total += chunk.len();
+
+            self.reader.consume(consumed);
+            if done {
+                break;
+            }
+        }
+
+        Ok(Some(Line {
+            text: String::from_utf8_lossy(&kept).into_owned(),
+            rest: (total > MAX_LINE_BYTES).then(|| tentative.finish()),
+            trimmed: (len > MAX_LINE_BYTES).then(|| committed.finish()),
+        }))
+    }
+
+    /// 继续读取到 `WINDOW_LINES` 行，返回之后是否还有未读取的内容
+    ///
+    /// 读到整个输出的末尾时去掉末尾的空白和空行。
+    fn fill(&mut self, lines: &mut Vec<Line>) -> io::Result<bool> {
+        while lines.len() < WINDOW_LINES {
+            match self.next()? {
+                Some(line) => lines.push(line),
+                None => break,
+            }
+        }
+        let more = lines.len() == WINDOW_LINES && !self.rest_is_blank()?;
+        if !more {
+            while lines.last().is_some_and(Line::is_blank) {
+                lines.pop();
+            }
+            if let Some(last) = lines.pop() {
+                lines.push(last.trim_end());
+            }
+        }
+        Ok(more)
+    }
+
+    /// 剩余内容是否只有空白，读到的行会保留给之后的 `next`
+    fn rest_is_blank(&mut self) -> io::Result<bool> {
+        if self.pending.is_some() {
+            return Ok(false);
+        }
+        while let Some(line) = self.read()? {
+            if !line.is_blank() {
+                self.pending = Some(line);
+                return Ok(false);
+            }
+            // 之后最多展示 `WINDOW_LINES` 行，更多的空白行只需记录行数
+            if self.ahead.len() < WINDOW_LINES {
+                self.ahead.push_back(line);
+            } else {
+                self.skipped += 1;
+            }
+        }
+        Ok(true)
+    }
+}
@@ -0,0 +1,41 @@
+use crate::config::settings;
+use crate::judge::language::Language;
+use crate::judge::sandbox::{Limits, Sandbox};
+use anyhow::Result;
+
+/// 检查判题环境是否可用：沙箱工作目录可以创建，`health.languages` 中各语言的
+/// 工具链都能在沙箱中启动
+pub async fn check() -> Result<()> {
+    let sandbox = Sandbox::new();
+    sandbox
+        .prepare()
+        .map_err(|e| anyhow::anyhow!("Sandbox is unavailable: {}", e))?;
+
+    let limits = Limits::compile();
+    for name in &settings().health.languages {
+        let language = Language::resolve(name)?;
+        for program in language.toolchain() {
+            let version_arg = if program == "go" {
+                "version"
+            } else {
+                "--version"
+            };
+            let output = sandbox
+                .execute(&[program.clone(), version_arg.to_string()], &limits)
+                .await
+                .map_err(|e| {
+                    anyhow::anyhow!("Toolchain {} for {} is unavailable: {}", program, name, e)
+                })?;
+            if !output.success() {
+                return Err(anyhow::anyhow!(
+                    "Toolchain {} for {} is unavailable: {}",
+                    program,
+                    name,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
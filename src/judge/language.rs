@@ -0,0 +1,226 @@
+use crate::config::settings;
+use anyhow::Result;
+
+/// 语言大类，同一大类的不同变体共享源文件名和运行方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LanguageFamily {
+    C,
+    Cpp,
+    Python,
+    PyPy,
+    Java,
+    Go,
+    Rust,
+    JavaScript,
+    TypeScript,
+}
+
+/// 具体的语言变体，例如 `cpp17`、`java11`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Language {
+    pub name: &'static str,
+    pub family: LanguageFamily,
+    standard: Option<&'static str>, // 语言标准，如 c++17、Java release 版本
+}
+
+/// 所有支持的语言变体
+pub const LANGUAGE_VARIANTS: &[&str] = &[
+    "c99",
+    "c11",
+    "c17",
+    "cpp11",
+    "cpp14",
+    "cpp17",
+    "cpp20",
+    "python3",
+    "pypy3",
+    "java11",
+    "java17",
+    "go",
+    "rust",
+    "javascript",
+    "typescript",
+];
+
+impl Language {
+    /// 根据提交中的语言名称解析语言变体，未指定版本时使用默认变体
+    pub fn resolve(name: &str) -> Result<Self> {
+        use LanguageFamily::*;
+
+        let (name, family, standard) = match name.to_lowercase().as_str() {
+            "c99" => ("c99", C, Some("c99")),
+            "c" | "c11" => ("c11", C, Some("c11")),
+            "c17" => ("c17", C, Some("c17")),
+            "cpp11" => ("cpp11", Cpp, Some("c++11")),
+            "cpp14" => ("cpp14", Cpp, Some("c++14")),
+            "cpp" | "c++" | "cpp17" => ("cpp17", Cpp, Some("c++17")),
+            "cpp20" => ("cpp20", Cpp, Some("c++20")),
+            "python" | "python3" => ("python3", Python, None),
+            "pypy3" => ("pypy3", PyPy, None),
+            "java" | "java11" => ("java11", Java, Some("11")),
+            "java17" => ("java17", Java, Some("17")),
+            "go" => ("go", Go, None),
+            "rust" => ("rust", Rust, None),
+            "javascript" | "js" => ("javascript", JavaScript, None),
+            "typescript" | "ts" => ("typescript", TypeScript, None),
+            _ => return Err(anyhow::anyhow!("Unsupported language")),
+        };
+
+        Ok(Self {
+            name,
+            family,
+            standard,
+        })
+    }
+
+    pub fn source_file(&self) -> &'static str {
+        match self.family {
+            LanguageFamily::C => "solution.c",
+            LanguageFamily::Cpp => "solution.cpp",
+            LanguageFamily::Python | LanguageFamily::PyPy => "solution.py",
+            LanguageFamily::Java => "Main.java",
+            LanguageFamily::Go => "solution.go",
+            LanguageFamily::Rust => "solution.rs",
+            LanguageFamily::JavaScript => "solution.js",
+            LanguageFamily::TypeScript => "solution.ts",
+        }
+    }
+
+    /// 编译命令，首项为程序名；解释型语言返回 `None`
+    pub fn compile_command(&self) -> Option<Vec<String>> {
+        let source_file = self.source_file();
+        let command: Vec<String> = match self.family {
+            LanguageFamily::C => vec![
+                "gcc".into(),
+                format!("-O{}", settings().c.opt_level),
+                format!("-std={}", self.standard.unwrap_or("c11")),
+                "-DONLINE_JUDGE".into(),
+                "-o".into(),
+                "solution".into(),
+                source_file.into(),
+                "-lm".into(),
+            ],
+            LanguageFamily::Cpp => vec![
+                "g++".into(),
+                format!("-O{}", settings().cpp.opt_level),
+                format!("-std={}", self.standard.unwrap_or("c++17")),
+                "-DONLINE_JUDGE".into(),
+                "-o".into(),
+                "solution".into(),
+                source_file.into(),
+            ],
+            LanguageFamily::Java => {
+                let mut command = vec!["javac".to_string()];
+                if let Some(release) = self.standard {
+                    command.push("--release".into());
+                    command.push(release.into());
+                }
+                command.push("-encoding".into());
+                command.push("UTF-8".into());
+                command.push(source_file.into());
+                command
+            }
+            LanguageFamily::Go => vec![
+                "go".into(),
+                "build".into(),
+                "-o".into(),
+                "solution".into(),
+                source_file.into(),
+            ],
+            LanguageFamily::Rust => {
+                let rust = &settings().rust;
+                let mut command = vec![
+                    "rustc".to_string(),
+                    "--edition".into(),
+                    rust.edition.clone(),
+                    "-C".into(),
+                    format!("opt-level={}", rust.opt_level),
+                    "--cfg".into(),
+                    "online_judge".into(),
+                    "--color".into(),
+                    "never".into(),
+                    "-o".into(),
+                    "solution".into(),
+                ];
+                // 链接离线预编译的第三方 crate
+                if let Some(vendor_dir) = &rust.vendor_dir {
+                    command.push("-L".into());
+                    command.push(vendor_dir.display().to_string());
+                    for name in &rust.vendor_crates {
+                        command.push("--extern".into());
+                        command.push(name.clone());
+                    }
+                }
+                command.push(source_file.into());
+                command
+            }
+            // 离线转译为 solution.js
+            LanguageFamily::TypeScript => vec![
+                settings().javascript.tsc.clone(),
+                "--pretty".into(),
+                "false".into(),
+                "--target".into(),
+                "es2020".into(),
+                "--module".into(),
+                "commonjs".into(),
+                "--outDir".into(),
+                ".".into(),
+                source_file.into(),
+            ],
+            LanguageFamily::Python | LanguageFamily::PyPy | LanguageFamily::JavaScript => {
+                return None
+            }
+        };
+        Some(command)
+    }
+
+    /// 运行命令，首项为程序名
+    pub fn run_command(&self, memory_limit: u64) -> Vec<String> {
+        match self.family {
+            LanguageFamily::C | LanguageFamily::Cpp | LanguageFamily::Go | LanguageFamily::Rust => {
+                vec!["./solution".into()]
+            }
+            LanguageFamily::Python => {
+                let python = if cfg!(windows) { "python" } else { "python3" };
+                vec![python.into(), "solution.py".into()]
+            }
+            LanguageFamily::PyPy => vec!["pypy3".into(), "solution.py".into()],
+            LanguageFamily::Java => vec!["java".into(), "-cp".into(), ".".into(), "Main".into()],
+            LanguageFamily::JavaScript | LanguageFamily::TypeScript => {
+                // 堆上限取内存限制减去预留给 V8 自身的部分
+                let heap_mb = (memory_limit / 1024 / 1024)
+                    .saturating_sub(settings().javascript.heap_reserve_mb)
+                    .max(16);
+                vec![
+                    "node".into(),
+                    format!("--max-old-space-size={}", heap_mb),
+                    "solution.js".into(),
+                ]
+            }
+        }
+    }
+
+    /// 编译和运行所需的外部程序，不含编译产物本身
+    pub fn toolchain(&self) -> Vec<String> {
+        let mut programs: Vec<String> = self
+            .compile_command()
+            .map(|command| command[0].clone())
+            .into_iter()
+            .collect();
+        let run = self.run_command(0).swap_remove(0);
+        if !run.starts_with("./") && !programs.contains(&run) {
+            programs.push(run);
+        }
+        programs
+    }
+
+    /// 时间限制倍数，JIT 语言需要放宽以补偿预热时间
+    pub fn time_multiplier(&self) -> f64 {
+        match self.family {
+            LanguageFamily::JavaScript | LanguageFamily::TypeScript => {
+                settings().javascript.time_multiplier
+            }
+            _ => 1.0,
+        }
+    }
+}
@@ -1,39 +1,98 @@
+mod answer;
+pub mod cache;
 mod checker;
+mod diff;
+pub mod health;
+pub mod language;
 mod runner;
+mod sandbox;
+mod stress;
+pub mod toolchain;
 mod types;
+mod validator;
 
+use crate::config::settings;
+use crate::logging;
+use crate::metrics::metrics;
+pub use answer::{AnswerGenerator, ReferenceRun};
 use anyhow::Result;
 use checker::Checker;
+use language::Language;
 use runner::Runner;
-use std::env;
-use std::time::Duration;
+pub use sandbox::remove_work_dirs;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::time::{Duration, Instant};
+pub use stress::{Counterexample, StressReport, StressTest};
+use tracing::{debug, info, info_span, warn, Instrument};
 pub use types::*;
+pub use validator::Validator;
 
 pub struct Judge {
     runner: Runner,
     checker: Checker,
+    language: &'static str, // 语言变体名称，用于指标标签
+    output_diff: bool,
 }
 
 impl Judge {
     pub fn new(config: JudgeConfig) -> Self {
-        let runner = Runner::new(config.clone());
-        let checker = Checker::new();
-        Self { runner, checker }
+        let language = Language::resolve(&config.language)
+            .map(|language| language.name)
+            .unwrap_or("unknown");
+        let runner = Runner::new(config);
+        let checker = Checker::default();
+        Self {
+            runner,
+            checker,
+            language,
+            output_diff: false,
+        }
+    }
+
+    /// 语言变体名称，无法识别时为 `unknown`
+    pub fn language(&self) -> &'static str {
+        self.language
+    }
+
+    /// 使用指定的答案比较方式
+    pub fn with_checker(mut self, spec: CheckerSpec) -> Self {
+        self.checker = Checker::new(spec);
+        self
+    }
+
+    /// 答案错误时计算与期望输出的差异，比赛中通常不向选手展示，默认关闭
+    pub fn with_output_diff(mut self, enabled: bool) -> Self {
+        self.output_diff = enabled;
+        self
+    }
+
+    /// 是否复用编译缓存，默认开启
+    pub fn with_artifact_cache(mut self, enabled: bool) -> Self {
+        self.runner = self.runner.with_cache(enabled);
+        self
     }
 
     pub async fn judge(&self, test_case: &TestCase) -> Result<JudgeResult> {
-        self.judge_all(&[test_case.clone()]).await
+        self.judge_all(std::slice::from_ref(test_case)).await
     }
 
+    /// 编译并依次评测所有测试点，日志位于携带语言的 `judge` span 下，
+    /// 每个测试点另有携带序号（从 1 开始）的 `test_case` span
     pub async fn judge_all(&self, test_cases: &[TestCase]) -> Result<JudgeResult> {
-        println!("开始判题...");
+        let span = info_span!("judge", language = self.language);
+        self.judge_all_inner(test_cases).instrument(span).await
+    }
 
-        let print_details = env::var("PRINT_TESTCASE_DETAILS")
-            .map(|v| v.to_lowercase() == "true")
-            .unwrap_or(false);
+    async fn judge_all_inner(&self, test_cases: &[TestCase]) -> Result<JudgeResult> {
+        info!("开始判题...");
 
-        if let Err(e) = self.runner.compile().await {
-            println!("编译错误: {}", e);
+        let compile_start = Instant::now();
+        let compiled = self.runner.compile().await;
+        metrics().observe_compile(self.language, compile_start.elapsed());
+        if let Err(e) = compiled {
+            info!("编译错误: {}", e);
             return Ok(JudgeResult {
                 status: JudgeStatus::CompilationError,
                 time_used: Default::default(),
@@ -42,7 +101,19 @@ impl Judge {
                 test_case_results: vec![],
             });
         }
-        println!("编译成功!");
+        info!("编译成功!");
+
+        if let Err(e) = self.checker.prepare().await {
+            warn!("评测程序准备失败: {}", e);
+            metrics().record_sandbox_failure("checker");
+            return Ok(JudgeResult {
+                status: JudgeStatus::SystemError,
+                time_used: Default::default(),
+                memory_used: 0,
+                error_message: Some(e.to_string()),
+                test_case_results: vec![],
+            });
+        }
 
         let mut test_case_results = Vec::new();
         let mut max_time = Duration::default();
@@ -50,58 +121,23 @@ impl Judge {
         let mut final_status = JudgeStatus::Accepted;
 
         for (i, test_case) in test_cases.iter().enumerate() {
-            println!("\n测试点 #{}", i + 1);
-
-            if print_details {
-                println!("输入数据:\n{}", test_case.input.trim());
-                println!("预期输出:\n{}", test_case.expected_output.trim());
-            }
+            let span = info_span!("test_case", index = i + 1);
+            let result = self.judge_test_case(i, test_case).instrument(span).await?;
 
-            match self.runner.run(&test_case.input).await {
-                Ok((output, time_used, memory_used)) => {
-                    println!("运行时间: {:?}", time_used);
-                    println!("内存使用: {} bytes", memory_used);
-
-                    if print_details {
-                        println!("实际输出:\n{}", output.trim());
-                    }
-
-                    let status = self.checker.check(test_case, &output);
-                    println!("判题结果: {:?}", status);
-
-                    max_time = max_time.max(time_used);
-                    max_memory = max_memory.max(memory_used);
-
-                    if status != JudgeStatus::Accepted {
-                        final_status = status.clone();
-                    }
-
-                    test_case_results.push(TestCaseResult {
-                        status,
-                        time_used,
-                        memory_used,
-                        actual_output: output,
-                        test_case_id: i,
-                    });
-                }
-                Err(e) => {
-                    println!("运行错误: {}", e);
-                    test_case_results.push(TestCaseResult {
-                        status: JudgeStatus::RuntimeError,
-                        time_used: Duration::default(),
-                        memory_used: 0,
-                        actual_output: e.to_string(),
-                        test_case_id: i,
-                    });
-                    final_status = JudgeStatus::RuntimeError;
-                }
+            max_time = max_time.max(result.time_used);
+            max_memory = max_memory.max(result.memory_used);
+            if result.status != JudgeStatus::Accepted {
+                final_status = result.status.clone();
             }
+            test_case_results.push(result);
         }
 
-        println!("\n判题完成!");
-        println!("最终状态: {:?}", final_status);
-        println!("最大运行时间: {:?}", max_time);
-        println!("最大内存使用: {} bytes", max_memory);
+        info!(
+            status = ?final_status,
+            max_time = ?max_time,
+            max_memory = max_memory,
+            "判题完成"
+        );
 
         Ok(JudgeResult {
             status: final_status,
@@ -111,4 +147,122 @@ impl Judge {
             test_case_results,
         })
     }
+
+    async fn judge_test_case(&self, id: usize, test_case: &TestCase) -> Result<TestCaseResult> {
+        debug!(
+            input = %logging::test_data(&test_case.input),
+            expected_output = %logging::test_data(&test_case.expected_output),
+            "测试数据"
+        );
+
+        let run = match self.runner.run_with_args(&test_case.input, &[]).await {
+            Ok(run) => run,
+            Err(e) => {
+                warn!("沙箱运行失败: {}", e);
+                metrics().record_sandbox_failure("run");
+                return Ok(TestCaseResult {
+                    status: JudgeStatus::SystemError,
+                    time_used: Duration::default(),
+                    memory_used: 0,
+                    actual_output: e.to_string().into_bytes(),
+                    test_case_id: id,
+                    output_diff: None,
+                });
+            }
+        };
+
+        let (time_used, memory_used) = (run.time_used, run.memory_used);
+        metrics().observe_run(self.language, time_used);
+
+        let output = Self::read_output(&run.output_path)?;
+        debug!(actual_output = %logging::output(&output), "实际输出");
+
+        let status = if run.timed_out {
+            JudgeStatus::TimeLimitExceeded
+        } else if !run.success() {
+            JudgeStatus::RuntimeError
+        } else {
+            self.checker
+                .check(test_case, &run.output_path)
+                .await
+                .unwrap_or_else(|e| {
+                    warn!("评测程序错误: {}", e);
+                    metrics().record_sandbox_failure("checker");
+                    JudgeStatus::SystemError
+                })
+        };
+        info!(
+            status = ?status,
+            time_used = ?time_used,
+            memory_used = memory_used,
+            "测试点评测完成"
+        );
+
+        let output_diff = if self.output_diff && status == JudgeStatus::WrongAnswer {
+            diff::output_diff(test_case, &run.output_path).unwrap_or_else(|e| {
+                warn!("计算输出差异失败: {}", e);
+                None
+            })
+        } else {
+            None
+        };
+
+        Ok(TestCaseResult {
+            status,
+            time_used,
+            memory_used,
+            actual_output: output,
+            test_case_id: id,
+            output_diff,
+        })
+    }
+
+    /// 以自定义输入编译并运行一次，返回程序的输出，不进行答案比较
+    pub async fn run(&self, stdin: &TestData) -> Result<RunResult> {
+        let span = info_span!("run", language = self.language);
+        self.run_inner(stdin).instrument(span).await
+    }
+
+    async fn run_inner(&self, stdin: &TestData) -> Result<RunResult> {
+        debug!(stdin = %logging::test_data(stdin), "自定义输入");
+        if let Err(e) = self.runner.compile().await {
+            info!("编译错误: {}", e);
+            return Ok(RunResult {
+                status: JudgeStatus::CompilationError,
+                time_used: Duration::default(),
+                memory_used: 0,
+                exit_code: None,
+                stdout: Vec::new(),
+                stderr: Vec::new(),
+                error_message: Some(e.to_string()),
+            });
+        }
+
+        let output = self.runner.run_with_args(stdin, &[]).await?;
+        let status = if output.timed_out {
+            JudgeStatus::TimeLimitExceeded
+        } else if output.success() {
+            JudgeStatus::Accepted
+        } else {
+            JudgeStatus::RuntimeError
+        };
+
+        Ok(RunResult {
+            status,
+            time_used: output.time_used,
+            memory_used: output.memory_used,
+            exit_code: output.exit_code,
+            stdout: Self::read_output(&output.output_path)?,
+            stderr: Self::read_output(&output.error_path)?,
+            error_message: None,
+        })
+    }
+
+    /// 读取程序输出用于返回给调用方，超过长度上限的部分会被截断
+    fn read_output(path: &Path) -> Result<Vec<u8>> {
+        let limit = settings().judge.output_limit_kb * 1024;
+        let mut output = Vec::new();
+        File::open(path)?.take(limit).read_to_end(&mut output)?;
+        Ok(output)
+    }
 }
@@ -1,14 +1,18 @@
-use crate::judge::JudgeConfig;
+use crate::config::settings;
+use crate::judge::cache::ArtifactCache;
+use crate::judge::language::Language;
+use crate::judge::sandbox::{kill_process, limit_file_size, Limits, Sandbox};
+use crate::judge::{JudgeConfig, TestData};
 use anyhow::Result;
-use std::fs;
-use std::io::Write;
-use std::path::Path;
+use std::fs::{self, File};
+use std::path::PathBuf;
 use std::process::Command;
 use std::process::Stdio;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::thread;
 use std::time::{Duration, Instant};
+use tracing::warn;
 
 #[cfg(target_os = "windows")]
 use winapi::um::{
@@ -20,114 +24,163 @@ use winapi::um::{
 #[cfg(target_os = "linux")]
 use std::fs::read_to_string;
 
+const INPUT_FILE: &str = ".stdin";
+const OUTPUT_FILE: &str = ".stdout";
+const ERROR_FILE: &str = ".stderr";
+
+/// 评测任务被中止时杀死尚未回收的子进程并停止内存监控线程
+struct RunGuard {
+    pid: Option<u32>, // 子进程回收后为 None
+    should_stop: Arc<AtomicBool>,
+}
+
+impl Drop for RunGuard {
+    fn drop(&mut self) {
+        if let Some(pid) = self.pid {
+            kill_process(pid);
+        }
+        self.should_stop.store(true, Ordering::SeqCst);
+    }
+}
+
+/// 单次运行的结果，程序的 stdout 和 stderr 分别保存在 `output_path`、`error_path` 指向的文件中
+pub struct RunOutput {
+    pub output_path: PathBuf,
+    pub error_path: PathBuf,
+    pub time_used: Duration,
+    pub memory_used: u64,
+    pub exit_code: Option<i32>, // 被信号终止或超时时为 None
+    pub timed_out: bool,
+    pub output_limit_exceeded: bool, // stdout 或 stderr 写到了文件大小上限，按运行错误处理
+}
+
+impl RunOutput {
+    pub fn success(&self) -> bool {
+        !self.timed_out && !self.output_limit_exceeded && self.exit_code == Some(0)
+    }
+}
+
 pub struct Runner {
     config: JudgeConfig,
+    sandbox: Sandbox,
+    extra_files: Vec<PathBuf>, // 编译前复制到工作目录的附加文件，如 testlib.h
+    use_cache: bool,
 }
 
 impl Runner {
     pub fn new(config: JudgeConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            sandbox: Sandbox::new(),
+            extra_files: Vec::new(),
+            use_cache: true,
+        }
+    }
+
+    /// 是否使用编译缓存，自检时关闭以确保真正调用编译器
+    pub fn with_cache(mut self, enabled: bool) -> Self {
+        self.use_cache = enabled;
+        self
+    }
+
+    pub fn with_files(mut self, files: Vec<PathBuf>) -> Self {
+        self.extra_files = files;
+        self
     }
 
     pub async fn compile(&self) -> Result<()> {
-        match self.config.language.as_str() {
-            "python" => {
-                fs::write("solution.py", &self.config.source_code)?;
-                // 检查 Python 是否可用
-                let python_cmd = if cfg!(windows) { "python" } else { "python3" };
-                let status = Command::new(python_cmd)
-                    .arg("-c")
-                    .arg("print('test')")
-                    .output()?;
-
-                if !status.status.success() {
-                    return Err(anyhow::anyhow!("Python interpreter not found"));
+        let language = Language::resolve(&self.config.language)?;
+        let source_file = self.sandbox.path().join(language.source_file());
+
+        self.sandbox.prepare()?;
+        fs::write(&source_file, &self.config.source_code)?;
+
+        let mut cache_sources = vec![self.config.source_code.as_bytes().to_vec()];
+        let mut excluded = vec![language.source_file().to_string()];
+        for file in &self.extra_files {
+            let name = file
+                .file_name()
+                .ok_or_else(|| anyhow::anyhow!("Invalid file {}", file.display()))?;
+            let content = fs::read(file)?;
+            fs::write(self.sandbox.path().join(name), &content)?;
+            cache_sources.push(content);
+            excluded.push(name.to_string_lossy().into_owned());
+        }
+
+        let Some(compile_command) = language.compile_command() else {
+            return Ok(());
+        };
+
+        // 相同源代码直接复用缓存的编译产物
+        let cache = ArtifactCache::from_settings().filter(|_| self.use_cache);
+        let cache_key = ArtifactCache::key(&language, &compile_command, &cache_sources);
+        if let Some(cache) = &cache {
+            match cache.restore(&cache_key, self.sandbox.path()) {
+                Ok(true) => {
+                    fs::remove_file(&source_file).ok();
+                    return Ok(());
                 }
+                Ok(false) => {}
+                Err(e) => warn!("读取编译缓存失败: {}", e),
             }
-            "cpp" => {
-                let (source_file, exec_file) = ("solution.cpp", "solution");
-
-                fs::write(source_file, &self.config.source_code)?;
-
-                let status = Command::new("g++")
-                    .arg(source_file)
-                    .arg("-o")
-                    .arg(exec_file)
-                    .output()?;
-
-                if !status.status.success() {
-                    return Err(anyhow::anyhow!(
-                        "Compilation error: {}",
-                        String::from_utf8_lossy(&status.stderr)
-                    ));
-                }
+        }
 
-                // 清理源代码文件
-                fs::remove_file(source_file).ok();
-            }
-            "c" => {
-                let (source_file, exec_file) = ("solution.c", "solution");
-
-                fs::write(source_file, &self.config.source_code)?;
-
-                let status = Command::new("gcc")
-                    .arg(source_file)
-                    .arg("-o")
-                    .arg(exec_file)
-                    .output()?;
-
-                if !status.status.success() {
-                    return Err(anyhow::anyhow!(
-                        "Compilation error: {}",
-                        String::from_utf8_lossy(&status.stderr)
-                    ));
-                }
+        let limits = Limits::compile();
+        let output = self.sandbox.execute(&compile_command, &limits).await?;
 
-                // 清理源代码文件
-                fs::remove_file(source_file).ok();
+        if output.timed_out() {
+            return Err(anyhow::anyhow!(
+                "Compilation error: compile time limit exceeded ({} ms)",
+                limits.time.as_millis()
+            ));
+        }
+
+        if !output.success() {
+            // 部分编译器（如 tsc）把错误信息输出在 stdout
+            let mut message = format!(
+                "{}{}",
+                String::from_utf8_lossy(&output.stdout),
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            if Self::is_out_of_memory(&message) {
+                return Err(anyhow::anyhow!(
+                    "Compilation error: compile memory limit exceeded ({} MB)",
+                    limits.memory / 1024 / 1024
+                ));
             }
-            "java" => {
-                fs::write("Main.java", &self.config.source_code)?;
 
-                let status = Command::new("javac").arg("Main.java").output()?;
+            if output.output_truncated {
+                message.push_str("\n... (compiler output truncated)");
+            }
 
-                if !status.status.success() {
-                    return Err(anyhow::anyhow!(
-                        "Compilation error: {}",
-                        String::from_utf8_lossy(&status.stderr)
-                    ));
-                }
+            return Err(anyhow::anyhow!("Compilation error: {}", message));
+        }
 
-                // 清理源代码文件
-                fs::remove_file("Main.java").ok();
-            }
-            "go" => {
-                let (source_file, exec_file) = ("solution.go", "solution");
-
-                fs::write(source_file, &self.config.source_code)?;
-
-                let status = Command::new("go")
-                    .arg("build")
-                    .arg("-o")
-                    .arg(exec_file)
-                    .arg(source_file)
-                    .output()?;
-
-                if !status.status.success() {
-                    return Err(anyhow::anyhow!(
-                        "Compilation error: {}",
-                        String::from_utf8_lossy(&status.stderr)
-                    ));
-                }
+        // 清理源代码文件
+        fs::remove_file(&source_file).ok();
 
-                // 清理源代码文件
-                fs::remove_file(source_file).ok();
+        if let Some(cache) = &cache {
+            if let Err(e) = cache.store(&cache_key, self.sandbox.path(), &excluded) {
+                warn!("写入编译缓存失败: {}", e);
             }
-            _ => return Err(anyhow::anyhow!("Unsupported language")),
         }
+
         Ok(())
     }
 
+    fn is_out_of_memory(message: &str) -> bool {
+        [
+            "out of memory",
+            "memory exhausted",
+            "Cannot allocate memory",
+            "bad_alloc",
+            "Could not reserve enough space",
+        ]
+        .iter()
+        .any(|pattern| message.contains(pattern))
+    }
+
     #[cfg(target_os = "linux")]
     fn get_memory_usage(pid: u32) -> Result<u64> {
         let status_file = format!("/proc/{}/status", pid);
@@ -183,52 +236,49 @@ impl Runner {
         }
     }
 
-    pub async fn run(&self, input: &str) -> Result<(String, Duration, u64)> {
+    /// 运行程序，stdin 直接读取测试输入文件，stdout、stderr 写入工作目录中的文件
+    ///
+    /// 非零退出码和超时都作为正常结果返回，只有沙箱本身出错时返回错误。
+    pub async fn run_with_args(&self, input: &TestData, args: &[String]) -> Result<RunOutput> {
+        let stdin = File::open(self.materialize(INPUT_FILE, input)?)?;
+        let output_path = self.sandbox.path().join(OUTPUT_FILE);
+        let stdout = File::create(&output_path)?;
+        let error_path = self.sandbox.path().join(ERROR_FILE);
+        let stderr = File::create(&error_path)?;
+
         let start = Instant::now();
 
-        let mut command = match self.config.language.as_str() {
-            "cpp" | "c" => Command::new("./solution"),
-            "python" => {
-                let mut cmd = if cfg!(windows) {
-                    Command::new("python")
-                } else {
-                    Command::new("python3")
-                };
-                cmd.arg("solution.py");
-                cmd
-            }
-            "java" => {
-                let mut cmd = Command::new("java");
-                cmd.arg("Main");
-                cmd
-            }
-            "go" => Command::new("./solution"),
-            _ => return Err(anyhow::anyhow!("Unsupported language")),
-        };
+        let language = Language::resolve(&self.config.language)?;
+        let run_command = language.run_command(self.config.memory_limit);
+        let mut command = Command::new(self.sandbox.resolve_program(&run_command[0]));
+        command
+            .args(&run_command[1..])
+            .args(args)
+            .current_dir(self.sandbox.path());
+        // 上限至少比返回给调用方的输出长度大，截断的输出不会被误判为超限
+        let judge = &settings().judge;
+        let file_limit =
+            (judge.output_file_limit_mb * 1024 * 1024).max(judge.output_limit_kb * 1024 + 1);
+        limit_file_size(&mut command, file_limit);
 
         let mut child = command
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
+            .stdin(Stdio::from(stdin))
+            .stdout(Stdio::from(stdout))
+            .stderr(Stdio::from(stderr))
             .spawn()?;
 
         let pid = child.id();
+        let should_stop = Arc::new(AtomicBool::new(false));
+        let mut guard = RunGuard {
+            pid: Some(pid),
+            should_stop: should_stop.clone(),
+        };
 
-        // 写入输入后立即关闭 stdin
-        {
-            let mut stdin = child
-                .stdin
-                .take()
-                .ok_or_else(|| anyhow::anyhow!("Failed to open stdin"))?;
-            stdin.write_all(input.as_bytes())?;
-            // stdin 在这里会自动关闭
-        }
-
-        // 设置超时检查
-        let timeout = tokio::time::sleep(self.config.time_limit);
+        // 设置超时检查，JIT 语言按倍数放宽时间限制
+        let time_limit = self.config.time_limit.mul_f64(language.time_multiplier());
+        let timeout = tokio::time::sleep(time_limit);
         tokio::pin!(timeout);
 
-        let should_stop = Arc::new(AtomicBool::new(false));
         let should_stop_clone = should_stop.clone();
         let memory_usage = Arc::new(AtomicU64::new(0));
         let memory_usage_clone = memory_usage.clone();
@@ -245,50 +295,64 @@ impl Runner {
         });
 
         // 使用 tokio 的 spawn_blocking 来等待子进程
-        let output = tokio::task::spawn_blocking(move || child.wait_with_output());
+        let mut output = tokio::task::spawn_blocking(move || child.wait());
 
         tokio::select! {
-            result = output => {
+            result = &mut output => {
+                guard.pid = None;
                 should_stop.store(true, Ordering::SeqCst);
                 monitoring.join().ok();
 
-                let output = result??;
+                let status = result??;
                 let duration = start.elapsed();
                 let max_memory = memory_usage.load(Ordering::SeqCst);
-
-                if !output.status.success() {
-                    return Err(anyhow::anyhow!("Runtime error"));
+                let output_limit_exceeded = [&output_path, &error_path]
+                    .iter()
+                    .any(|path| fs::metadata(path).is_ok_and(|m| m.len() >= file_limit));
+                if output_limit_exceeded {
+                    warn!("程序输出超过文件大小上限 {} bytes", file_limit);
                 }
 
-                Ok((String::from_utf8(output.stdout)?, duration, max_memory))
+                Ok(RunOutput {
+                    output_path,
+                    error_path,
+                    time_used: duration,
+                    memory_used: max_memory,
+                    exit_code: status.code(),
+                    timed_out: false,
+                    output_limit_exceeded,
+                })
             }
             _ = timeout => {
+                // 超时后杀死进程并回收，避免继续占用 CPU
+                kill_process(pid);
+                output.await.ok();
+                guard.pid = None;
                 should_stop.store(true, Ordering::SeqCst);
                 monitoring.join().ok();
-                Err(anyhow::anyhow!("Time limit exceeded"))
+
+                Ok(RunOutput {
+                    output_path,
+                    error_path,
+                    time_used: time_limit,
+                    memory_used: memory_usage.load(Ordering::SeqCst),
+                    exit_code: None,
+                    timed_out: true,
+                    output_limit_exceeded: false,
+                })
             }
         }
     }
-}
-
-impl Drop for Runner {
-    fn drop(&mut self) {
-        // 清理所有可能的临时文件
-        let files = match self.config.language.as_str() {
-            "cpp" | "c" => vec!["solution", "solution.exe"],
-            "python" => vec!["solution.py", "__pycache__"],
-            "java" => vec!["Main.class", "Main.java"],
-            "go" => vec!["solution", "solution.exe"],
-            _ => vec![],
-        };
 
-        for file in files {
-            if Path::new(file).exists() {
-                if file == "__pycache__" {
-                    fs::remove_dir_all(file).ok();
-                } else {
-                    fs::remove_file(file).ok();
-                }
+    /// 返回测试数据在磁盘上的路径，内存中的数据写入工作目录下的 `name` 文件
+    pub fn materialize(&self, name: &str, data: &TestData) -> Result<PathBuf> {
+        match data {
+            TestData::File(path) => Ok(path.clone()),
+            TestData::Inline(data) => {
+                self.sandbox.prepare()?;
+                let path = self.sandbox.path().join(name);
+                fs::write(&path, data)?;
+                Ok(path)
             }
         }
     }
@@ -0,0 +1,270 @@
+use crate::config::settings;
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::Command;
+
+static SANDBOX_ID: AtomicU64 = AtomicU64::new(0);
+
+/// 进程资源限制
+#[derive(Debug, Clone)]
+pub struct Limits {
+    pub time: Duration, // 墙钟时间限制
+    pub memory: u64,    // 数据段内存限制(bytes)
+    pub output: usize,  // stdout/stderr 各自保留的最大字节数
+}
+
+impl Limits {
+    /// 编译阶段的资源限制
+    pub fn compile() -> Self {
+        let compile = &settings().compile;
+        Self {
+            time: Duration::from_millis(compile.time_limit_ms),
+            memory: compile.memory_limit_mb * 1024 * 1024,
+            output: compile.output_limit_kb * 1024,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ExecOutput {
+    pub status: Option<ExitStatus>, // 超时被杀时为 None
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+    pub output_truncated: bool,
+}
+
+impl ExecOutput {
+    pub fn timed_out(&self) -> bool {
+        self.status.is_none()
+    }
+
+    pub fn success(&self) -> bool {
+        self.status.is_some_and(|status| status.success())
+    }
+}
+
+/// 沙箱：每个判题任务独立的工作目录，以及带资源限制的进程执行
+///
+/// 工作目录在首次 [`Sandbox::prepare`] 时创建，沙箱销毁时整体删除。
+pub struct Sandbox {
+    work_dir: PathBuf,
+}
+
+impl Sandbox {
+    pub fn new() -> Self {
+        let id = SANDBOX_ID.fetch_add(1, Ordering::SeqCst);
+        let work_root = &settings().sandbox.work_root;
+        // 使用绝对路径，保证在子进程的工作目录中也能访问
+        let work_dir = std::path::absolute(work_root)
+            .unwrap_or_else(|_| work_root.clone())
+            .join(format!("{}-{}", std::process::id(), id));
+        Self { work_dir }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.work_dir
+    }
+
+    pub fn prepare(&self) -> Result<()> {
+        fs::create_dir_all(&self.work_dir)?;
+        Ok(())
+    }
+
+    /// 将命令中的 `./` 相对路径解析到工作目录下
+    pub fn resolve_program(&self, program: &str) -> PathBuf {
+        match program.strip_prefix("./") {
+            Some(name) => self.work_dir.join(name),
+            None => PathBuf::from(program),
+        }
+    }
+
+    /// 在工作目录中执行命令，超时后杀死整个进程组
+    pub async fn execute(&self, command: &[String], limits: &Limits) -> Result<ExecOutput> {
+        let (program, args) = command
+            .split_first()
+            .ok_or_else(|| anyhow::anyhow!("Empty command"))?;
+
+        let mut cmd = Command::new(self.resolve_program(program));
+        cmd.args(args)
+            .current_dir(&self.work_dir)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+
+        #[cfg(unix)]
+        {
+            let memory = limits.memory;
+            cmd.process_group(0);
+            // SAFETY: pre_exec 中只调用 async-signal-safe 的 setrlimit
+            unsafe {
+                cmd.pre_exec(move || {
+                    set_rlimit(libc::RLIMIT_DATA, memory)?;
+                    set_rlimit(libc::RLIMIT_CORE, 0)?;
+                    Ok(())
+                });
+            }
+        }
+
+        let mut child = cmd.spawn()?;
+        let pid = child.id();
+        // kill_on_drop 只结束直接子进程，任务被中止时还需结束整个进程组
+        let mut group = ProcessGroupGuard(pid);
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+
+        let wait = async {
+            let (stdout, stderr, status) = tokio::join!(
+                read_capped(stdout, limits.output),
+                read_capped(stderr, limits.output),
+                child.wait()
+            );
+            (stdout, stderr, status)
+        };
+
+        let finished = tokio::time::timeout(limits.time, wait).await;
+        group.0 = None;
+        match finished {
+            Ok(((stdout, out_truncated), (stderr, err_truncated), status)) => Ok(ExecOutput {
+                status: Some(status?),
+                stdout,
+                stderr,
+                output_truncated: out_truncated || err_truncated,
+            }),
+            Err(_) => {
+                kill_process_group(pid);
+                child.kill().await.ok();
+                Ok(ExecOutput {
+                    status: None,
+                    stdout: Vec::new(),
+                    stderr: Vec::new(),
+                    output_truncated: false,
+                })
+            }
+        }
+    }
+}
+
+impl Default for Sandbox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        // 清理工作目录及其中所有临时文件
+        if self.work_dir.exists() {
+            fs::remove_dir_all(&self.work_dir).ok();
+        }
+    }
+}
+
+/// 删除本进程创建的所有沙箱工作目录，用于退出前清理未正常销毁的沙箱
+pub fn remove_work_dirs() -> Result<()> {
+    let work_root = &settings().sandbox.work_root;
+    if !work_root.is_dir() {
+        return Ok(());
+    }
+
+    let prefix = format!("{}-", std::process::id());
+    for entry in fs::read_dir(work_root)? {
+        let entry = entry?;
+        if entry.file_name().to_string_lossy().starts_with(&prefix) {
+            fs::remove_dir_all(entry.path()).ok();
+        }
+    }
+    Ok(())
+}
+
+/// 读取管道内容，超过上限的部分继续读出但直接丢弃，避免子进程写阻塞
+async fn read_capped<R: AsyncRead + Unpin>(reader: Option<R>, limit: usize) -> (Vec<u8>, bool) {
+    let Some(mut reader) = reader else {
+        return (Vec::new(), false);
+    };
+
+    let mut data = Vec::new();
+    let mut truncated = false;
+    let mut buf = [0u8; 8192];
+    while let Ok(n) = reader.read(&mut buf).await {
+        if n == 0 {
+            break;
+        }
+        let keep = n.min(limit.saturating_sub(data.len()));
+        data.extend_from_slice(&buf[..keep]);
+        truncated |= keep < n;
+    }
+    (data, truncated)
+}
+
+/// 限制 `command` 启动的进程能写出的文件大小，写到上限时进程收到 SIGXFSZ
+#[cfg(unix)]
+pub fn limit_file_size(command: &mut std::process::Command, bytes: u64) {
+    use std::os::unix::process::CommandExt;
+    // SAFETY: pre_exec 中只调用 async-signal-safe 的 setrlimit
+    unsafe {
+        command.pre_exec(move || {
+            set_rlimit(libc::RLIMIT_FSIZE, bytes)?;
+            set_rlimit(libc::RLIMIT_CORE, 0)?;
+            Ok(())
+        });
+    }
+}
+
+#[cfg(not(unix))]
+pub fn limit_file_size(_command: &mut std::process::Command, _bytes: u64) {}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+type Resource = libc::__rlimit_resource_t;
+#[cfg(all(unix, not(all(target_os = "linux", target_env = "gnu"))))]
+type Resource = libc::c_int;
+
+#[cfg(unix)]
+fn set_rlimit(resource: Resource, value: u64) -> std::io::Result<()> {
+    let limit = libc::rlimit {
+        rlim_cur: value as libc::rlim_t,
+        rlim_max: value as libc::rlim_t,
+    };
+    if unsafe { libc::setrlimit(resource, &limit) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn kill_process_group(pid: Option<u32>) {
+    if let Some(pid) = pid {
+        // 进程组 ID 与子进程 PID 相同，负数表示向整个进程组发送信号
+        unsafe {
+            libc::kill(-(pid as i32), libc::SIGKILL);
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn kill_process_group(_pid: Option<u32>) {}
+
+/// 未正常结束（如评测任务被中止）时杀死整个进程组
+struct ProcessGroupGuard(Option<u32>);
+
+impl Drop for ProcessGroupGuard {
+    fn drop(&mut self) {
+        kill_process_group(self.0);
+    }
+}
+
+/// 强制结束单个进程
+#[cfg(unix)]
+pub fn kill_process(pid: u32) {
+    unsafe {
+        libc::kill(pid as i32, libc::SIGKILL);
+    }
+}
+
+#[cfg(not(unix))]
+pub fn kill_process(_pid: u32) {}
@@ -0,0 +1,146 @@
+use crate::judge::checker::Checker;
+use crate::judge::runner::{RunOutput, Runner};
+use crate::judge::types::{CheckerSpec, JudgeConfig, JudgeStatus, TestCase, TestData};
+use crate::judge::Judge;
+use anyhow::Result;
+use std::ops::Range;
+use tracing::info;
+
+/// 对拍：用生成器按种子生成输入，比较参考解与候选解的输出
+///
+/// 生成器按 testlib 约定以种子作为唯一的命令行参数运行，输出即为测试输入。
+/// 参考解的输出作为标准答案，候选解的输出用题目的 checker 比较。
+pub struct StressTest {
+    generator: Runner,
+    reference: Runner,
+    candidates: Vec<Runner>,
+    checker: Checker,
+}
+
+/// 对拍结果
+#[derive(Debug, Clone)]
+pub struct StressReport {
+    pub tests_run: usize,
+    pub counterexample: Option<Counterexample>,
+}
+
+/// 第一个使候选解出错的输入
+#[derive(Debug, Clone)]
+pub struct Counterexample {
+    pub seed: u64,
+    pub candidate: usize, // 候选解下标
+    pub status: JudgeStatus,
+    pub input: Vec<u8>,
+    pub expected_output: Vec<u8>, // 参考解输出
+    pub actual_output: Vec<u8>,   // 候选解输出
+}
+
+impl StressTest {
+    pub fn new(
+        generator: JudgeConfig,
+        reference: JudgeConfig,
+        candidates: Vec<JudgeConfig>,
+    ) -> Self {
+        Self {
+            generator: Runner::new(generator),
+            reference: Runner::new(reference),
+            candidates: candidates.into_iter().map(Runner::new).collect(),
+            checker: Checker::default(),
+        }
+    }
+
+    /// 使用题目的答案比较方式
+    pub fn with_checker(mut self, spec: CheckerSpec) -> Self {
+        self.checker = Checker::new(spec);
+        self
+    }
+
+    /// 依次使用 `seeds` 中的种子对拍，遇到第一个反例即停止
+    pub async fn run(&self, seeds: Range<u64>) -> Result<StressReport> {
+        self.generator
+            .compile()
+            .await
+            .map_err(|e| anyhow::anyhow!("Generator {}", e))?;
+        self.reference
+            .compile()
+            .await
+            .map_err(|e| anyhow::anyhow!("Reference {}", e))?;
+        for (i, candidate) in self.candidates.iter().enumerate() {
+            candidate
+                .compile()
+                .await
+                .map_err(|e| anyhow::anyhow!("Candidate #{} {}", i + 1, e))?;
+        }
+        self.checker.prepare().await?;
+
+        let mut tests_run = 0;
+        for seed in seeds {
+            let generated = self
+                .generator
+                .run_with_args(&TestData::Inline(Vec::new()), &[seed.to_string()])
+                .await?;
+            if !generated.success() {
+                return Err(anyhow::anyhow!("Generator failed with seed {}", seed));
+            }
+            let input = TestData::File(generated.output_path.clone());
+
+            let expected = self.reference.run_with_args(&input, &[]).await?;
+            if !expected.success() {
+                return Err(anyhow::anyhow!(
+                    "Reference solution failed with seed {}: {:?}",
+                    seed,
+                    Self::failure_status(&expected)
+                ));
+            }
+            let test_case = TestCase {
+                input,
+                expected_output: TestData::File(expected.output_path.clone()),
+            };
+
+            tests_run += 1;
+            for (candidate, runner) in self.candidates.iter().enumerate() {
+                let actual = runner.run_with_args(&test_case.input, &[]).await?;
+                let status = match Self::failure_status(&actual) {
+                    Some(status) => status,
+                    None => self.checker.check(&test_case, &actual.output_path).await?,
+                };
+                if status == JudgeStatus::Accepted {
+                    continue;
+                }
+
+                info!(
+                    "种子 {} 上候选解 #{} 结果为 {:?}",
+                    seed,
+                    candidate + 1,
+                    status
+                );
+                return Ok(StressReport {
+                    tests_run,
+                    counterexample: Some(Counterexample {
+                        seed,
+                        candidate,
+                        status,
+                        input: Judge::read_output(&generated.output_path)?,
+                        expected_output: Judge::read_output(&expected.output_path)?,
+                        actual_output: Judge::read_output(&actual.output_path)?,
+                    }),
+                });
+            }
+        }
+
+        Ok(StressReport {
+            tests_run,
+            counterexample: None,
+        })
+    }
+
+    fn failure_status(output: &RunOutput) -> Option<JudgeStatus> {
+        if output.timed_out {
+            Some(JudgeStatus::TimeLimitExceeded)
+        } else if !output.success() {
+            Some(JudgeStatus::RuntimeError)
+        } else {
+            None
+        }
+    }
+}
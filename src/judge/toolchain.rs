@@ -0,0 +1,133 @@
+use crate::config::settings;
+use crate::judge::cache::{compiler_version, forget_compiler_versions};
+use crate::judge::language::{Language, LanguageFamily};
+use crate::judge::{Judge, JudgeConfig, JudgeStatus, TestCase};
+use std::sync::RwLock;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// 最近一次自检的结果，按 `languages.enabled` 的顺序排列
+static STATUS: RwLock<Vec<LanguageStatus>> = RwLock::new(Vec::new());
+
+/// 单个语言变体的自检结果
+#[derive(Debug, Clone)]
+pub struct LanguageStatus {
+    pub name: &'static str,
+    pub available: bool,
+    pub version: String,       // 编译器版本，解释型语言为解释器版本
+    pub error: Option<String>, // 不可用的原因
+}
+
+/// 对所有启用的语言编译并运行 hello world，记录编译器版本，不可用的语言随之禁用
+///
+/// 每次自检都重新查询编译器版本并跳过编译缓存，工具链升级或损坏后刷新即可反映。
+pub async fn self_test() -> Vec<LanguageStatus> {
+    forget_compiler_versions();
+    let mut results = Vec::new();
+    for name in &settings().languages.enabled {
+        let language = match Language::resolve(name) {
+            Ok(language) => language,
+            Err(e) => {
+                warn!("配置中的语言 {} 无法识别: {}", name, e);
+                continue;
+            }
+        };
+        if results
+            .iter()
+            .any(|s: &LanguageStatus| s.name == language.name)
+        {
+            continue;
+        }
+
+        let status = test_language(&language).await;
+        match &status.error {
+            None => info!(language = status.name, version = %status.version, "语言自检通过"),
+            Some(e) => warn!(language = status.name, "语言自检失败，已禁用: {}", e),
+        }
+        results.push(status);
+    }
+
+    *STATUS.write().unwrap() = results.clone();
+    results
+}
+
+/// 最近一次自检的结果，尚未自检时为空
+pub fn languages() -> Vec<LanguageStatus> {
+    STATUS.read().unwrap().clone()
+}
+
+/// 语言是否被禁用：未在 `languages.enabled` 中启用，或自检未通过
+///
+/// 无法识别的语言名称不视为禁用，由评测流程报告编译错误；尚未自检时只按配置判断。
+pub fn is_disabled(name: &str) -> bool {
+    let Ok(language) = Language::resolve(name) else {
+        return false;
+    };
+    let enabled = settings()
+        .languages
+        .enabled
+        .iter()
+        .any(|name| Language::resolve(name).is_ok_and(|l| l.name == language.name));
+    if !enabled {
+        return true;
+    }
+    STATUS
+        .read()
+        .unwrap()
+        .iter()
+        .any(|status| status.name == language.name && !status.available)
+}
+
+async fn test_language(language: &Language) -> LanguageStatus {
+    let version = language
+        .toolchain()
+        .first()
+        .map(|program| compiler_version(language, program))
+        .unwrap_or_default();
+
+    let judge = Judge::new(JudgeConfig {
+        time_limit: Duration::from_secs(10),
+        memory_limit: 1024 * 1024 * 1024,
+        language: language.name.to_string(),
+        source_code: hello_world(language.family).to_string(),
+    })
+    .with_artifact_cache(false);
+    let test_case = TestCase {
+        input: "".into(),
+        expected_output: "hello\n".into(),
+    };
+
+    let error = match judge.judge(&test_case).await {
+        Ok(result) if result.status == JudgeStatus::Accepted => None,
+        Ok(result) => Some(
+            result
+                .error_message
+                .unwrap_or_else(|| format!("hello world finished with {:?}", result.status)),
+        ),
+        Err(e) => Some(e.to_string()),
+    };
+
+    LanguageStatus {
+        name: language.name,
+        available: error.is_none(),
+        version,
+        error,
+    }
+}
+
+fn hello_world(family: LanguageFamily) -> &'static str {
+    match family {
+        LanguageFamily::C => "#include <stdio.h>\nint main(void) { puts(\"hello\"); return 0; }\n",
+        LanguageFamily::Cpp => {
+            "#include <iostream>\nint main() { std::cout << \"hello\" << std::endl; }\n"
+        }
+        LanguageFamily::Python | LanguageFamily::PyPy => "print(\"hello\")\n",
+        LanguageFamily::Java => {
+            "public class Main { public static void main(String[] args) { System.out.println(\"hello\"); } }\n"
+        }
+        LanguageFamily::Go => "package main\n\nimport \"fmt\"\n\nfunc main() { fmt.Println(\"hello\") }\n",
+        LanguageFamily::Rust => "fn main() { println!(\"hello\"); }\n",
+        LanguageFamily::JavaScript => "console.log(\"hello\");\n",
+        LanguageFamily::TypeScript => "const message: string = \"hello\";\nconsole.log(message);\n",
+    }
+}
@@ -1,4 +1,9 @@
+use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
 use std::time::Duration;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,6 +15,38 @@ pub struct JudgeResult {
     pub test_case_results: Vec<TestCaseResult>,
 }
 
+impl JudgeResult {
+    /// 按子任务计算得分，子任务中的测试点全部通过才能获得该子任务的分数
+    ///
+    /// 没有子任务时满分为 100，按通过的测试点比例计分。
+    pub fn score(&self, subtasks: &[Subtask]) -> f64 {
+        let accepted = |id: &usize| {
+            self.test_case_results
+                .iter()
+                .any(|r| r.test_case_id == *id && r.status == JudgeStatus::Accepted)
+        };
+
+        if subtasks.is_empty() {
+            let total = self.test_case_results.len();
+            if total == 0 {
+                return 0.0;
+            }
+            let passed = self
+                .test_case_results
+                .iter()
+                .filter(|r| r.status == JudgeStatus::Accepted)
+                .count();
+            return 100.0 * passed as f64 / total as f64;
+        }
+
+        subtasks
+            .iter()
+            .filter(|subtask| subtask.test_cases.iter().all(accepted))
+            .map(|subtask| subtask.score)
+            .sum()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum JudgeStatus {
     Accepted,
@@ -23,8 +60,58 @@ pub enum JudgeStatus {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TestCase {
-    pub input: String,
-    pub expected_output: String,
+    pub input: TestData,
+    pub expected_output: TestData,
+}
+
+/// 测试数据来源：内存中的数据，或磁盘上的文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum TestData {
+    Inline(Vec<u8>),
+    File(PathBuf),
+}
+
+impl TestData {
+    /// 以流的方式读取数据，文件不会整体载入内存
+    pub fn reader(&self) -> Result<Box<dyn BufRead + '_>> {
+        Ok(match self {
+            TestData::Inline(data) => Box::new(data.as_slice()),
+            TestData::File(path) => Box::new(BufReader::new(File::open(path)?)),
+        })
+    }
+}
+
+impl From<Vec<u8>> for TestData {
+    fn from(data: Vec<u8>) -> Self {
+        TestData::Inline(data)
+    }
+}
+
+impl From<String> for TestData {
+    fn from(data: String) -> Self {
+        TestData::Inline(data.into_bytes())
+    }
+}
+
+impl From<&str> for TestData {
+    fn from(data: &str) -> Self {
+        TestData::Inline(data.as_bytes().to_vec())
+    }
+}
+
+impl From<PathBuf> for TestData {
+    fn from(path: PathBuf) -> Self {
+        TestData::File(path)
+    }
+}
+
+impl fmt::Display for TestData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TestData::Inline(data) => write!(f, "{}", String::from_utf8_lossy(data).trim()),
+            TestData::File(path) => write!(f, "<{}>", path.display()),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,8 +119,79 @@ pub struct TestCaseResult {
     pub status: JudgeStatus,
     pub time_used: Duration,
     pub memory_used: u64,
-    pub actual_output: String,
+    pub actual_output: Vec<u8>, // 实际输出的原始字节，超过长度上限时截断
+    pub test_case_id: usize,
+    pub output_diff: Option<OutputDiff>, // 答案错误时与期望输出的差异，未开启时为 None
+}
+
+/// 答案错误时实际输出与期望输出的第一处不一致
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutputDiff {
+    pub line: usize,          // 从 1 开始
+    pub column: usize,        // 从 1 开始，按字符计
+    pub unified_diff: String, // 不一致处附近的统一差异格式片段
+    pub truncated: bool,      // 差异超出展示范围，只展示了一部分
+}
+
+/// 自定义输入运行的结果，不经过答案比较
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunResult {
+    pub status: JudgeStatus, // 正常退出为 Accepted，另有 CompilationError、TimeLimitExceeded、RuntimeError
+    pub time_used: Duration,
+    pub memory_used: u64,
+    pub exit_code: Option<i32>,        // 被信号终止或超时时为 None
+    pub stdout: Vec<u8>,               // 超过长度上限时截断
+    pub stderr: Vec<u8>,               // 超过长度上限时截断
+    pub error_message: Option<String>, // 编译错误信息
+}
+
+/// 答案比较方式
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CheckerSpec {
+    /// 忽略首尾空白逐字节比较
+    #[default]
+    Exact,
+    /// 按空白分隔逐个比较单词，对应 testlib 的 wcmp
+    Tokens,
+    /// 数字按绝对或相对误差比较，其余单词精确比较，对应 testlib 的 rcmp
+    Float { precision: f64 },
+    /// 特殊评测程序
+    Custom {
+        language: String,
+        source_code: String,
+        style: CheckerStyle,
+    },
+}
+
+/// 特殊评测程序的调用约定
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckerStyle {
+    Testlib, // checker <input> <output> <answer>，退出码 0 为正确
+    Hustoj,  // spj <input> <answer> <output>，退出码 0 为正确
+}
+
+/// 子任务：一组测试点全部通过才能获得分数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Subtask {
+    pub name: String,
+    pub score: f64,
+    pub test_cases: Vec<usize>, // 测试点下标，从 0 开始
+}
+
+/// 测试数据校验程序，按 testlib validator 约定从 stdin 读取输入，退出码 0 为合法
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidatorSpec {
+    pub language: String,
+    pub source_code: String,
+}
+
+/// 单个测试点的校验错误
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidationError {
     pub test_case_id: usize,
+    pub message: String, // 校验程序的 stderr 输出
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,74 @@
+use crate::config::settings;
+use crate::judge::checker::testlib_files;
+use crate::judge::runner::Runner;
+use crate::judge::types::{JudgeConfig, TestCase, ValidationError, ValidatorSpec};
+use anyhow::Result;
+use std::fs::File;
+use std::io::Read;
+use std::time::Duration;
+
+/// 校验程序 stderr 保留的最大字节数
+const MESSAGE_LIMIT: u64 = 4096;
+
+/// 测试数据校验器
+///
+/// 按 testlib validator 的约定逐个测试点运行：输入从 stdin 读取，退出码 0 表示合法，
+/// 否则 stderr 中的内容作为错误信息。
+pub struct Validator {
+    program: Runner,
+}
+
+impl Validator {
+    pub fn new(spec: &ValidatorSpec) -> Self {
+        let validator = &settings().validator;
+        let config = JudgeConfig {
+            time_limit: Duration::from_millis(validator.time_limit_ms),
+            memory_limit: validator.memory_limit_mb * 1024 * 1024,
+            language: spec.language.clone(),
+            source_code: spec.source_code.clone(),
+        };
+        Self {
+            program: Runner::new(config).with_files(testlib_files()),
+        }
+    }
+
+    /// 校验所有测试点的输入，返回不合法的测试点；校验程序无法编译或运行时返回错误
+    pub async fn validate_all(&self, test_cases: &[TestCase]) -> Result<Vec<ValidationError>> {
+        self.program
+            .compile()
+            .await
+            .map_err(|e| anyhow::anyhow!("Validator {}", e))?;
+
+        let mut errors = Vec::new();
+        for (test_case_id, test_case) in test_cases.iter().enumerate() {
+            let result = self.program.run_with_args(&test_case.input, &[]).await?;
+            if result.timed_out {
+                return Err(anyhow::anyhow!(
+                    "Validator time limit exceeded on test case {}",
+                    test_case_id + 1
+                ));
+            }
+            if result.success() {
+                continue;
+            }
+
+            let mut stderr = Vec::new();
+            File::open(&result.error_path)?
+                .take(MESSAGE_LIMIT)
+                .read_to_end(&mut stderr)?;
+            let mut message = String::from_utf8_lossy(&stderr).trim().to_string();
+            if message.is_empty() {
+                message = match result.exit_code {
+                    Some(code) => format!("Validator exited with code {}", code),
+                    None => "Validator was killed by a signal".to_string(),
+                };
+            }
+            errors.push(ValidationError {
+                test_case_id,
+                message,
+            });
+        }
+
+        Ok(errors)
+    }
+}
@@ -1,3 +1,11 @@
+pub mod auth;
+pub mod config;
 pub mod judge;
+pub mod limits;
+pub mod logging;
+pub mod metrics;
+pub mod problem;
+pub mod queue;
+pub mod submission;
 
 pub use judge::{Judge, JudgeConfig, JudgeResult, JudgeStatus, TestCase};
@@ -0,0 +1,112 @@
+use crate::config::LimitsSettings;
+
+/// 单次请求的资源规模，用于检查是否超出 `limits` 配置的上限
+#[derive(Debug, Clone, Default)]
+pub struct RequestSize {
+    pub time_limit_ms: i64,
+    pub memory_limit_mb: i64,
+    pub source_bytes: usize,
+    pub test_cases: usize,  // 请求中附带的测试点数，引用题目数据时为 0
+    pub input_bytes: usize, // 请求中附带的所有输入数据的总大小
+}
+
+impl RequestSize {
+    /// 返回第一个不满足的限制，消息可直接作为 INVALID_ARGUMENT 的说明；上限为 0 表示不限制
+    pub fn check(&self, limits: &LimitsSettings) -> Result<(), String> {
+        if self.time_limit_ms <= 0 {
+            return Err(format!("时间限制必须大于0，实际为 {}", self.time_limit_ms));
+        }
+        if self.memory_limit_mb <= 0 {
+            return Err(format!(
+                "内存限制必须大于0，实际为 {}",
+                self.memory_limit_mb
+            ));
+        }
+        exceeds(
+            "时间限制",
+            self.time_limit_ms as u64,
+            limits.max_time_limit_ms,
+            "ms",
+        )?;
+        exceeds(
+            "内存限制",
+            self.memory_limit_mb as u64,
+            limits.max_memory_limit_mb,
+            "MB",
+        )?;
+        exceeds(
+            "源代码大小",
+            self.source_bytes as u64,
+            limits.max_source_kb * 1024,
+            "bytes",
+        )?;
+        exceeds(
+            "测试点数",
+            self.test_cases as u64,
+            limits.max_test_cases as u64,
+            "个",
+        )?;
+        exceeds(
+            "输入数据总大小",
+            self.input_bytes as u64,
+            limits.max_input_mb * 1024 * 1024,
+            "bytes",
+        )?;
+        Ok(())
+    }
+}
+
+/// 检查流式上传的分块所属的测试点编号，返回可用作下标的编号
+///
+/// 每个编号都会创建一个临时文件，收到分块时立即拒绝超出范围的编号，不必等到发布时。
+pub fn check_test_case_id(test_case_id: i32, test_cases: usize) -> Result<usize, String> {
+    match usize::try_from(test_case_id) {
+        Ok(id) if id < test_cases => Ok(id),
+        _ => Err(format!(
+            "测试点编号 {} 超出范围 [0, {})",
+            test_case_id, test_cases
+        )),
+    }
+}
+
+/// 检查上传、导入或生成答案时的题目数据规模，`total_bytes` 为目前已收到的数据大小
+///
+/// 流式上传时每收到一块数据检查一次，超出上限后立即拒绝，不必等全部收完。
+pub fn check_problem_data(
+    test_cases: usize,
+    total_bytes: u64,
+    limits: &LimitsSettings,
+) -> Result<(), String> {
+    exceeds(
+        "测试点数",
+        test_cases as u64,
+        limits.max_test_cases as u64,
+        "个",
+    )?;
+    exceeds(
+        "题目数据总大小",
+        total_bytes,
+        limits.max_problem_mb * 1024 * 1024,
+        "bytes",
+    )
+}
+
+/// gRPC 单条消息的大小上限：输入数据和源代码的上限之和再留出 1 MB 给其他字段，
+/// 任一上限为 0（不限制）时不限制消息大小
+pub fn max_message_bytes(limits: &LimitsSettings) -> usize {
+    if limits.max_input_mb == 0 || limits.max_source_kb == 0 {
+        return usize::MAX;
+    }
+    let bytes = (limits.max_input_mb + 1) * 1024 * 1024 + limits.max_source_kb * 1024;
+    usize::try_from(bytes).unwrap_or(usize::MAX)
+}
+
+fn exceeds(what: &str, value: u64, max: u64, unit: &str) -> Result<(), String> {
+    if max > 0 && value > max {
+        return Err(format!(
+            "{} {} {} 超过上限 {} {}",
+            what, value, unit, max, unit
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use crate::config::{settings, LogFormat};
+use crate::judge::TestData;
+use std::fmt;
+use std::io::Read;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing_subscriber::EnvFilter;
+
+static SUBMISSION_SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// 按配置初始化日志系统，日志输出到 stderr，stdout 留给命令行的评测结果
+///
+/// 设置了 `RUST_LOG` 时优先使用其中的过滤规则，否则使用 `log.level`。
+pub fn init() {
+    let log = &settings().log;
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(&log.level));
+    let builder = tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_env_filter(filter)
+        .with_target(false)
+        .with_thread_ids(true)
+        .with_thread_names(true)
+        .with_file(true)
+        .with_line_number(true)
+        .with_level(true);
+
+    match log.format {
+        LogFormat::Text => builder.pretty().init(),
+        // 每条日志一行 JSON，附带所在的全部 span，便于日志系统按提交 ID 关联
+        LogFormat::Json => builder.json().with_span_list(true).init(),
+    }
+}
+
+/// 生成提交 ID，用于调用方未提供 ID 时关联同一次评测的日志
+pub fn submission_id() -> String {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let seq = SUBMISSION_SEQ.fetch_add(1, Ordering::SeqCst);
+    format!("{:x}-{:x}-{}", millis, std::process::id(), seq)
+}
+
+/// 测试数据在日志中的表示，未开启 `log.test_data` 时只记录长度
+pub fn test_data(data: &TestData) -> Redacted {
+    if !settings().log.test_data {
+        let size = match data {
+            TestData::Inline(data) => Some(data.len() as u64),
+            TestData::File(path) => std::fs::metadata(path).map(|m| m.len()).ok(),
+        };
+        return Redacted::Hidden(size);
+    }
+
+    let limit = settings().log.max_bytes;
+    let mut excerpt = Vec::new();
+    if let Ok(reader) = data.reader() {
+        reader.take(limit as u64 + 1).read_to_end(&mut excerpt).ok();
+    }
+    Redacted::Shown(excerpt)
+}
+
+/// 程序输出在日志中的表示，与测试数据使用相同的开关
+pub fn output(data: &[u8]) -> Redacted {
+    reveal(data, settings().log.test_data)
+}
+
+/// 源代码在日志中的表示，未开启 `log.source` 时只记录长度
+pub fn source(code: &str) -> Redacted {
+    reveal(code.as_bytes(), settings().log.source)
+}
+
+fn reveal(data: &[u8], shown: bool) -> Redacted {
+    if !shown {
+        return Redacted::Hidden(Some(data.len() as u64));
+    }
+    let limit = settings().log.max_bytes + 1;
+    Redacted::Shown(data[..data.len().min(limit)].to_vec())
+}
+
+/// 可能被脱敏的日志内容，展示时超过 `log.max_bytes` 的部分被截断
+pub enum Redacted {
+    Hidden(Option<u64>), // 数据长度，无法获取时为 None
+    Shown(Vec<u8>),      // 最多比上限多保留一个字节，用于判断是否截断
+}
+
+impl fmt::Display for Redacted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Redacted::Hidden(Some(size)) => write!(f, "<redacted {} bytes>", size),
+            Redacted::Hidden(None) => write!(f, "<redacted>"),
+            Redacted::Shown(data) => {
+                let limit = settings().log.max_bytes;
+                write!(
+                    f,
+                    "{}",
+                    String::from_utf8_lossy(&data[..data.len().min(limit)])
+                )?;
+                if data.len() > limit {
+                    write!(f, "...(truncated)")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
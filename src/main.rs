@@ -1,36 +1,155 @@
+mod cli;
 mod grpc;
 
+use crate::cli::{Cli, Commands};
+use crate::grpc::auth::{AuthInterceptor, MethodPathLayer};
 use crate::grpc::judge_grpc_service::judge_grpc_service_server::JudgeGrpcServiceServer;
 use crate::grpc::service::JudgeGrpcServiceImpl;
+use crate::grpc::{health, tls};
+use clap::Parser;
+use opti_neko::config::{self, settings};
+use opti_neko::limits::max_message_bytes;
+use opti_neko::{judge, logging, metrics};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tonic::service::interceptor::InterceptedService;
 use tonic::transport::Server;
-use tracing::{info, Level};
-use tracing_subscriber::FmtSubscriber;
+use tonic_health::ServingStatus;
+use tracing::{error, info, warn};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // 初始化日志系统
-    FmtSubscriber::builder()
-        .with_max_level(Level::INFO)
-        .with_target(false)
-        .with_thread_ids(true)
-        .with_file(true)
-        .with_line_number(true)
-        .with_thread_names(true)
-        .with_level(true)
-        .pretty()
-        .init();
+    let cli = Cli::parse();
 
+    config::init()?;
+    logging::init();
+
+    match cli.command.unwrap_or(Commands::Serve) {
+        Commands::Serve => serve().await,
+        Commands::Import {
+            format,
+            problem_id,
+            version,
+            path,
+        } => Ok(cli::import(format, &problem_id, &version, &path).await?),
+        Commands::Judge(args) => {
+            let accepted = cli::judge(args).await?;
+            if !accepted {
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        Commands::Stress(args) => {
+            let consistent = cli::stress(args).await?;
+            if !consistent {
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        Commands::Answers(args) => {
+            let saved = cli::answers(args).await?;
+            if !saved {
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+    }
+}
+
+async fn serve() -> Result<(), Box<dyn std::error::Error>> {
     info!("判题服务启动中...");
 
-    let addr = "0.0.0.0:50051".parse()?;
-    let my_service = JudgeGrpcServiceImpl::default();
+    let addr = settings().server.listen.parse()?;
+    let my_service = JudgeGrpcServiceImpl::new()?;
+    // 上次运行时中途退出而没有结果的提交，在接受请求之前记录为系统错误
+    my_service.fail_unjudged().await?;
+    let running = my_service.clone();
 
     info!("监听地址: {}", addr);
 
-    Server::builder()
-        .add_service(JudgeGrpcServiceServer::new(my_service))
-        .serve(addr)
-        .await?;
+    let languages = judge::toolchain::self_test().await;
+    let available = languages.iter().filter(|l| l.available).count();
+    info!("语言自检完成: {}/{} 可用", available, languages.len());
+
+    if settings().metrics.enabled {
+        tokio::spawn(async {
+            if let Err(e) = metrics::serve().await {
+                error!("指标服务启动失败: {}", e);
+            }
+        });
+    }
+
+    let (mut reporter, health_service) = tonic_health::server::health_reporter();
+    let monitor = tokio::spawn(health::monitor(reporter.clone()));
+
+    let mut builder = Server::builder();
+    if let Some(config) = tls::server_config(&settings().server.tls)? {
+        info!(
+            mtls = settings().server.tls.client_ca_path.is_some(),
+            "已启用 TLS"
+        );
+        builder = builder.tls_config(config)?;
+    }
+
+    let shutdown = Arc::new(Notify::new());
+    let server = builder
+        .layer(MethodPathLayer)
+        .add_service(health_service)
+        .add_service(InterceptedService::new(
+            // 默认的 4 MB 消息上限低于 limits.max_input_mb，按配置放宽
+            JudgeGrpcServiceServer::new(my_service)
+                .max_decoding_message_size(max_message_bytes(&settings().limits)),
+            AuthInterceptor::from_settings(),
+        ))
+        .serve_with_shutdown(addr, {
+            let shutdown = shutdown.clone();
+            async move { shutdown.notified().await }
+        });
+    tokio::pin!(server);
+
+    tokio::select! {
+        result = &mut server => result?,
+        _ = shutdown_signal() => {
+            // 先报告 NOT_SERVING，再停止接受新请求，等待进行中的评测结束
+            info!("收到退出信号，停止接受新的评测任务");
+            monitor.abort();
+            health::set_status(&mut reporter, ServingStatus::NotServing).await;
+            shutdown.notify_one();
+
+            let timeout = Duration::from_secs(settings().server.shutdown_timeout_secs);
+            match tokio::time::timeout(timeout, &mut server).await {
+                Ok(result) => result?,
+                // 调用方会收到 UNAVAILABLE 或连接错误并可重新提交
+                Err(_) => warn!("等待评测任务结束超时，中止剩余任务"),
+            }
+        }
+    }
+
+    // 中止仍在执行的任务（超时或调用方已断开连接）并等待子进程结束，之后才能清理工作目录
+    running.abort_running().await;
+
+    if let Err(e) = judge::remove_work_dirs() {
+        warn!("清理工作目录失败: {}", e);
+    }
+    info!("判题服务已退出");
 
     Ok(())
 }
+
+/// 等待 SIGTERM 或 Ctrl-C
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        let mut terminate =
+            tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+                .expect("failed to install SIGTERM handler");
+        tokio::select! {
+            _ = terminate.recv() => {}
+            _ = tokio::signal::ctrl_c() => {}
+        }
+    }
+
+    #[cfg(not(unix))]
+    tokio::signal::ctrl_c().await.ok();
+}
@@ -0,0 +1,184 @@
+use crate::config::settings;
+use anyhow::Result;
+use axum::routing::get;
+use axum::Router;
+use prometheus_client::encoding::text::encode;
+use prometheus_client::encoding::EncodeLabelSet;
+use prometheus_client::metrics::counter::Counter;
+use prometheus_client::metrics::family::Family;
+use prometheus_client::metrics::gauge::Gauge;
+use prometheus_client::metrics::histogram::{exponential_buckets, Histogram};
+use prometheus_client::registry::Registry;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tracing::info;
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct SubmissionLabels {
+    pub language: String,
+    pub verdict: String,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct LanguageLabels {
+    pub language: String,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct StageLabels {
+    pub stage: String, // compile、run 或 checker
+}
+
+type HistogramFamily = Family<LanguageLabels, Histogram, fn() -> Histogram>;
+
+/// 判题机的 Prometheus 指标
+pub struct Metrics {
+    registry: Registry,
+    submissions: Family<SubmissionLabels, Counter>,
+    compile_duration: HistogramFamily,
+    run_duration: HistogramFamily,
+    sandbox_failures: Family<StageLabels, Counter>,
+    queue_depth: Gauge,
+    workers: Gauge,
+    busy_workers: Gauge,
+}
+
+static METRICS: OnceLock<Metrics> = OnceLock::new();
+
+/// 全局指标，首次调用时注册
+pub fn metrics() -> &'static Metrics {
+    METRICS.get_or_init(Metrics::new)
+}
+
+fn duration_histogram() -> Histogram {
+    // 5ms 到约 40s
+    Histogram::new(exponential_buckets(0.005, 2.0, 14))
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let mut registry = Registry::with_prefix("opti_neko");
+
+        let submissions = Family::<SubmissionLabels, Counter>::default();
+        registry.register(
+            "submissions",
+            "Judged submissions by language and verdict",
+            submissions.clone(),
+        );
+        let compile_duration: HistogramFamily =
+            Family::new_with_constructor(duration_histogram as fn() -> Histogram);
+        registry.register(
+            "compile_duration_seconds",
+            "Compilation time by language",
+            compile_duration.clone(),
+        );
+        let run_duration: HistogramFamily =
+            Family::new_with_constructor(duration_histogram as fn() -> Histogram);
+        registry.register(
+            "run_duration_seconds",
+            "Per test case run time by language",
+            run_duration.clone(),
+        );
+        let sandbox_failures = Family::<StageLabels, Counter>::default();
+        registry.register(
+            "sandbox_failures",
+            "Sandbox or checker failures that ended in a system error",
+            sandbox_failures.clone(),
+        );
+        let queue_depth = Gauge::default();
+        registry.register(
+            "queue_depth",
+            "Tasks waiting in the judge queue",
+            queue_depth.clone(),
+        );
+        let workers = Gauge::default();
+        registry.register("workers", "Configured judge workers", workers.clone());
+        let busy_workers = Gauge::default();
+        registry.register(
+            "busy_workers",
+            "Judge workers currently running a task",
+            busy_workers.clone(),
+        );
+
+        Self {
+            registry,
+            submissions,
+            compile_duration,
+            run_duration,
+            sandbox_failures,
+            queue_depth,
+            workers,
+            busy_workers,
+        }
+    }
+
+    pub fn record_submission(&self, language: &str, verdict: &str) {
+        self.submissions
+            .get_or_create(&SubmissionLabels {
+                language: language.to_string(),
+                verdict: verdict.to_string(),
+            })
+            .inc();
+    }
+
+    pub fn observe_compile(&self, language: &str, duration: Duration) {
+        self.compile_duration
+            .get_or_create(&LanguageLabels {
+                language: language.to_string(),
+            })
+            .observe(duration.as_secs_f64());
+    }
+
+    pub fn observe_run(&self, language: &str, duration: Duration) {
+        self.run_duration
+            .get_or_create(&LanguageLabels {
+                language: language.to_string(),
+            })
+            .observe(duration.as_secs_f64());
+    }
+
+    pub fn record_sandbox_failure(&self, stage: &str) {
+        self.sandbox_failures
+            .get_or_create(&StageLabels {
+                stage: stage.to_string(),
+            })
+            .inc();
+    }
+
+    /// 更新队列长度和工作线程占用情况
+    pub fn set_queue(&self, waiting: usize, busy: usize, workers: usize) {
+        self.queue_depth.set(waiting as i64);
+        self.busy_workers.set(busy as i64);
+        self.workers.set(workers as i64);
+    }
+
+    /// 以 Prometheus 文本格式导出所有指标
+    pub fn encode(&self) -> String {
+        let mut buffer = String::new();
+        // 写入 String 不会失败
+        encode(&mut buffer, &self.registry).ok();
+        buffer
+    }
+}
+
+/// 启动 `/metrics` HTTP 服务
+pub async fn serve() -> Result<()> {
+    let addr = &settings().metrics.listen;
+    let app = Router::new().route(
+        "/metrics",
+        get(|| async {
+            (
+                [(
+                    "content-type",
+                    "application/openmetrics-text; version=1.0.0; charset=utf-8",
+                )],
+                metrics().encode(),
+            )
+        }),
+    );
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    info!("指标服务监听地址: {}", addr);
+    axum::serve(listener, app).await?;
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use super::{source_language, ProblemPackage};
+use crate::judge::{CheckerSpec, CheckerStyle, TestCase, TestData};
+use crate::problem::ProblemConfig;
+use anyhow::Result;
+use roxmltree::{Document, Node};
+use std::fs;
+use std::path::Path;
+
+/// 解析 FreeProblemSet XML，只导入文件中的第一道题目
+pub fn import(path: &Path) -> Result<ProblemPackage> {
+    let xml = fs::read_to_string(path)?;
+    let doc = Document::parse(&xml)?;
+    let item = doc
+        .descendants()
+        .find(|n| n.has_tag_name("item"))
+        .ok_or_else(|| anyhow::anyhow!("FPS file contains no problem item"))?;
+
+    let inputs: Vec<Node> = item
+        .children()
+        .filter(|n| n.has_tag_name("test_input"))
+        .collect();
+    let outputs: Vec<Node> = item
+        .children()
+        .filter(|n| n.has_tag_name("test_output"))
+        .collect();
+    if inputs.len() != outputs.len() {
+        return Err(anyhow::anyhow!(
+            "FPS test_input count {} does not match test_output count {}",
+            inputs.len(),
+            outputs.len()
+        ));
+    }
+
+    let test_cases: Vec<TestCase> = inputs
+        .iter()
+        .zip(&outputs)
+        .map(|(input, output)| TestCase {
+            input: TestData::from(input.text().unwrap_or_default()),
+            expected_output: TestData::from(output.text().unwrap_or_default()),
+        })
+        .collect();
+
+    let checker = match item.children().find(|n| n.has_tag_name("spj")) {
+        Some(spj) => CheckerSpec::Custom {
+            language: source_language(spj.attribute("language").unwrap_or("C++")),
+            source_code: spj.text().unwrap_or_default().to_string(),
+            style: CheckerStyle::Hustoj,
+        },
+        None => CheckerSpec::Exact,
+    };
+
+    let config = ProblemConfig {
+        name: child_text(item, "title").trim().to_string(),
+        time_limit_ms: limit(item, "time_limit", |value, unit| match unit {
+            "ms" => value,
+            _ => value * 1000.0,
+        }),
+        memory_limit_mb: limit(item, "memory_limit", |value, unit| match unit {
+            "kb" => value / 1024.0,
+            _ => value,
+        }),
+        subtasks: Vec::new(),
+        checker,
+        validator: None,
+    };
+
+    Ok(ProblemPackage::new(config, test_cases))
+}
+
+fn child_text<'a>(node: Node<'a, '_>, tag: &str) -> &'a str {
+    node.children()
+        .find(|n| n.has_tag_name(tag))
+        .and_then(|n| n.text())
+        .unwrap_or_default()
+}
+
+/// 读取带 unit 属性的限制，并换算为毫秒或 MB
+fn limit(item: Node, tag: &str, convert: impl Fn(f64, &str) -> f64) -> Option<u64> {
+    let node = item.children().find(|n| n.has_tag_name(tag))?;
+    let value: f64 = node.text()?.trim().parse().ok()?;
+    let unit = node.attribute("unit").unwrap_or_default().to_lowercase();
+    Some(convert(value, &unit).round() as u64)
+}
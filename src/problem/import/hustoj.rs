@@ -0,0 +1,49 @@
+use super::{input_files, source_language, ProblemPackage};
+use crate::judge::{CheckerSpec, CheckerStyle, TestCase, TestData};
+use crate::problem::ProblemConfig;
+use anyhow::Result;
+use std::fs;
+use std::path::Path;
+
+/// 解析 HUSTOJ 数据目录：按文件名配对的 `*.in`/`*.out`，以及可选的 `spj.c`/`spj.cc`
+pub fn import(dir: &Path) -> Result<ProblemPackage> {
+    let inputs = input_files(dir)?;
+
+    let mut test_cases = Vec::with_capacity(inputs.len());
+    for input in inputs {
+        let output = input.with_extension("out");
+        if !output.exists() {
+            return Err(anyhow::anyhow!(
+                "Answer file {} not found",
+                output.display()
+            ));
+        }
+        test_cases.push(TestCase {
+            input: TestData::File(input),
+            expected_output: TestData::File(output),
+        });
+    }
+
+    if test_cases.is_empty() {
+        return Err(anyhow::anyhow!("No .in files found"));
+    }
+
+    let mut checker = CheckerSpec::Exact;
+    for name in ["spj.cc", "spj.cpp", "spj.c"] {
+        let path = dir.join(name);
+        if path.exists() {
+            checker = CheckerSpec::Custom {
+                language: source_language(name),
+                source_code: fs::read_to_string(path)?,
+                style: CheckerStyle::Hustoj,
+            };
+            break;
+        }
+    }
+
+    let config = ProblemConfig {
+        checker,
+        ..Default::default()
+    };
+    Ok(ProblemPackage::new(config, test_cases))
+}
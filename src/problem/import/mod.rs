@@ -0,0 +1,196 @@
+mod fps;
+mod hustoj;
+mod polygon;
+
+use crate::config::settings;
+use crate::judge::TestCase;
+use crate::problem::ProblemConfig;
+use anyhow::Result;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static WORKSPACE_ID: AtomicU64 = AtomicU64::new(0);
+
+/// 支持导入的题目包格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackageFormat {
+    Polygon, // Codeforces Polygon 题目包
+    Fps,     // FreeProblemSet XML
+    Hustoj,  // HUSTOJ 的 .in/.out 数据目录
+}
+
+impl FromStr for PackageFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "polygon" => Ok(Self::Polygon),
+            "fps" => Ok(Self::Fps),
+            "hustoj" => Ok(Self::Hustoj),
+            _ => Err(format!("Unsupported package format: {}", s)),
+        }
+    }
+}
+
+/// 解析后的题目包，测试数据可能引用临时解压目录中的文件
+pub struct ProblemPackage {
+    pub config: ProblemConfig,
+    pub test_cases: Vec<TestCase>,
+    _workspace: Option<Workspace>,
+}
+
+/// 导入题目包
+///
+/// `path` 可以是解压后的目录、FPS 的 XML 文件，或 Polygon/HUSTOJ 的 zip 压缩包。
+/// 压缩包会解压到临时目录，在返回的 [`ProblemPackage`] 销毁时删除。
+pub fn import_package(format: PackageFormat, path: &Path) -> Result<ProblemPackage> {
+    if format == PackageFormat::Fps {
+        return fps::import(path);
+    }
+
+    if path.is_dir() {
+        return import_dir(format, path);
+    }
+
+    let workspace = Workspace::new()?;
+    extract_zip(path, workspace.path())?;
+
+    let root = find_root(workspace.path(), |dir| match format {
+        PackageFormat::Polygon => dir.join("problem.xml").exists(),
+        _ => has_extension(dir, "in"),
+    })?;
+    let mut package = import_dir(format, &root)?;
+    package._workspace = Some(workspace);
+    Ok(package)
+}
+
+fn import_dir(format: PackageFormat, dir: &Path) -> Result<ProblemPackage> {
+    match format {
+        PackageFormat::Polygon => polygon::import(dir),
+        PackageFormat::Hustoj => hustoj::import(dir),
+        PackageFormat::Fps => Err(anyhow::anyhow!("FPS package must be an XML file")),
+    }
+}
+
+impl ProblemPackage {
+    fn new(config: ProblemConfig, test_cases: Vec<TestCase>) -> Self {
+        Self {
+            config,
+            test_cases,
+            _workspace: None,
+        }
+    }
+}
+
+/// 导入过程中使用的临时目录，销毁时删除
+pub struct Workspace {
+    dir: PathBuf,
+}
+
+impl Workspace {
+    pub fn new() -> Result<Self> {
+        let dir = settings().sandbox.work_root.join(format!(
+            "import-{}-{}",
+            std::process::id(),
+            WORKSPACE_ID.fetch_add(1, Ordering::SeqCst)
+        ));
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir: std::path::absolute(&dir).unwrap_or(dir),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.dir
+    }
+}
+
+impl Drop for Workspace {
+    fn drop(&mut self) {
+        fs::remove_dir_all(&self.dir).ok();
+    }
+}
+
+fn extract_zip(archive: &Path, dest: &Path) -> Result<()> {
+    let mut archive = zip::ZipArchive::new(File::open(archive)?)?;
+    // extract 会拒绝包含 `..` 等越界路径的条目
+    archive.extract(dest)?;
+    Ok(())
+}
+
+/// 在解压目录及其下一层子目录中查找题目包的根目录
+fn find_root(dir: &Path, is_root: impl Fn(&Path) -> bool) -> Result<PathBuf> {
+    if is_root(dir) {
+        return Ok(dir.to_path_buf());
+    }
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() && is_root(&path) {
+            return Ok(path);
+        }
+    }
+    Err(anyhow::anyhow!("Problem package layout not recognized"))
+}
+
+fn has_extension(dir: &Path, extension: &str) -> bool {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .any(|entry| entry.path().extension().is_some_and(|e| e == extension))
+        })
+        .unwrap_or(false)
+}
+
+/// 目录中的 `*.in` 文件，按文件名自然排序
+pub fn input_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut inputs: Vec<PathBuf> = fs::read_dir(dir)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|e| e == "in"))
+        .collect();
+    inputs.sort_by_key(|path| natural_key(&path.file_name().unwrap_or_default().to_string_lossy()));
+    Ok(inputs)
+}
+
+/// 自然排序的比较键，使 `2.in` 排在 `10.in` 之前
+fn natural_key(name: &str) -> Vec<(String, u64)> {
+    let mut key = Vec::new();
+    let mut text = String::new();
+    let mut number = String::new();
+    for c in name.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+        } else {
+            if !number.is_empty() {
+                key.push((
+                    std::mem::take(&mut text),
+                    number.parse().unwrap_or(u64::MAX),
+                ));
+                number.clear();
+            }
+            text.push(c);
+        }
+    }
+    key.push((text, number.parse().unwrap_or(0)));
+    key
+}
+
+/// 根据 checker、validator 等辅助程序的源文件扩展名或语言描述推断语言变体
+fn source_language(hint: &str) -> String {
+    let hint = hint.to_lowercase();
+    let language = if hint.starts_with("python") || hint.ends_with(".py") {
+        "python3"
+    } else if hint.starts_with("java") || hint.ends_with(".java") {
+        "java17"
+    } else if hint == "c" || hint.starts_with("c.") || hint.ends_with(".c") {
+        "c11"
+    } else if hint.contains("20") || hint.contains("23") {
+        "cpp20"
+    } else {
+        "cpp17"
+    };
+    language.to_string()
+}
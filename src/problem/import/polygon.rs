@@ -0,0 +1,217 @@
+use super::{source_language, ProblemPackage};
+use crate::judge::{CheckerSpec, CheckerStyle, Subtask, TestCase, TestData, ValidatorSpec};
+use crate::problem::ProblemConfig;
+use anyhow::Result;
+use roxmltree::{Document, Node};
+use std::fs;
+use std::path::Path;
+
+/// 解析 Polygon 题目包（需包含生成后测试数据的完整包）
+pub fn import(dir: &Path) -> Result<ProblemPackage> {
+    let xml = fs::read_to_string(dir.join("problem.xml"))?;
+    let doc = Document::parse(&xml)?;
+    let root = doc.root_element();
+
+    let testset = root
+        .descendants()
+        .find(|n| n.has_tag_name("testset") && n.attribute("name") == Some("tests"))
+        .ok_or_else(|| anyhow::anyhow!("problem.xml has no tests testset"))?;
+
+    let time_limit_ms = child_text(testset, "time-limit").and_then(|t| t.parse().ok());
+    let memory_limit_mb = child_text(testset, "memory-limit")
+        .and_then(|m| m.parse::<u64>().ok())
+        .map(|bytes| bytes / 1024 / 1024);
+    let input_pattern = child_text(testset, "input-path-pattern").unwrap_or("tests/%02d");
+    let answer_pattern = child_text(testset, "answer-path-pattern").unwrap_or("tests/%02d.a");
+
+    let tests: Vec<Node> = testset
+        .children()
+        .find(|n| n.has_tag_name("tests"))
+        .map(|tests| {
+            tests
+                .children()
+                .filter(|n| n.has_tag_name("test"))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut test_cases = Vec::with_capacity(tests.len());
+    for index in 1..=tests.len() {
+        let input = dir.join(format_pattern(input_pattern, index));
+        let answer = dir.join(format_pattern(answer_pattern, index));
+        if !input.exists() || !answer.exists() {
+            return Err(anyhow::anyhow!(
+                "Test {} not found, generated tests require a full package",
+                index
+            ));
+        }
+        test_cases.push(TestCase {
+            input: TestData::File(input),
+            expected_output: TestData::File(answer),
+        });
+    }
+
+    let config = ProblemConfig {
+        name: problem_name(root),
+        time_limit_ms,
+        memory_limit_mb,
+        subtasks: subtasks(testset, &tests),
+        checker: checker(root, dir)?,
+        validator: validator(root, dir)?,
+    };
+
+    Ok(ProblemPackage::new(config, test_cases))
+}
+
+fn child_text<'a>(node: Node<'a, '_>, tag: &str) -> Option<&'a str> {
+    node.children()
+        .find(|n| n.has_tag_name(tag))
+        .and_then(|n| n.text())
+        .map(str::trim)
+}
+
+fn problem_name(root: Node) -> String {
+    let names: Vec<Node> = root
+        .descendants()
+        .filter(|n| n.has_tag_name("name") && n.parent().is_some_and(|p| p.has_tag_name("names")))
+        .collect();
+    names
+        .iter()
+        .find(|n| n.attribute("language") == Some("chinese"))
+        .or_else(|| names.first())
+        .and_then(|n| n.attribute("value"))
+        .or_else(|| root.attribute("short-name"))
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// 按测试点的 group 属性划分子任务，组分数缺省时为组内测试点分数之和
+fn subtasks(testset: Node, tests: &[Node]) -> Vec<Subtask> {
+    let mut subtasks: Vec<Subtask> = Vec::new();
+    for (index, test) in tests.iter().enumerate() {
+        let Some(group) = test.attribute("group") else {
+            continue;
+        };
+        let points: f64 = test
+            .attribute("points")
+            .and_then(|p| p.parse().ok())
+            .unwrap_or(0.0);
+
+        match subtasks.iter_mut().find(|s| s.name == group) {
+            Some(subtask) => {
+                subtask.test_cases.push(index);
+                subtask.score += points;
+            }
+            None => subtasks.push(Subtask {
+                name: group.to_string(),
+                score: points,
+                test_cases: vec![index],
+            }),
+        }
+    }
+
+    let groups = testset.children().find(|n| n.has_tag_name("groups"));
+    for group in groups.iter().flat_map(|g| g.children()) {
+        let (Some(name), Some(points)) = (group.attribute("name"), group.attribute("points"))
+        else {
+            continue;
+        };
+        if let Some(subtask) = subtasks.iter_mut().find(|s| s.name == name) {
+            subtask.score = points.parse().unwrap_or(subtask.score);
+        }
+    }
+
+    subtasks
+}
+
+/// 标准 checker 映射为内置比较方式，其余作为 testlib 特殊评测程序
+fn checker(root: Node, dir: &Path) -> Result<CheckerSpec> {
+    let Some(checker) = root.descendants().find(|n| n.has_tag_name("checker")) else {
+        return Ok(CheckerSpec::Tokens);
+    };
+
+    let builtin = match checker.attribute("name").unwrap_or_default() {
+        "std::wcmp.cpp" | "std::lcmp.cpp" | "std::ncmp.cpp" | "std::yesno.cpp" => {
+            Some(CheckerSpec::Tokens)
+        }
+        "std::fcmp.cpp" => Some(CheckerSpec::Exact),
+        "std::rcmp4.cpp" => Some(CheckerSpec::Float { precision: 1e-4 }),
+        "std::rcmp6.cpp" => Some(CheckerSpec::Float { precision: 1e-6 }),
+        "std::rcmp9.cpp" => Some(CheckerSpec::Float { precision: 1e-9 }),
+        "std::rcmp.cpp" => Some(CheckerSpec::Float { precision: 1.5e-6 }),
+        _ => None,
+    };
+    if let Some(builtin) = builtin {
+        return Ok(builtin);
+    }
+
+    let source = checker
+        .children()
+        .find(|n| n.has_tag_name("source"))
+        .ok_or_else(|| anyhow::anyhow!("Checker source not found in problem.xml"))?;
+    let path = source
+        .attribute("path")
+        .ok_or_else(|| anyhow::anyhow!("Checker source path missing"))?;
+
+    Ok(CheckerSpec::Custom {
+        language: source_language(source.attribute("type").unwrap_or(path)),
+        source_code: fs::read_to_string(dir.join(path))?,
+        style: CheckerStyle::Testlib,
+    })
+}
+
+/// 题目包中的第一个 validator
+fn validator(root: Node, dir: &Path) -> Result<Option<ValidatorSpec>> {
+    let source = root
+        .descendants()
+        .filter(|n| n.has_tag_name("validator"))
+        .find_map(|n| n.children().find(|c| c.has_tag_name("source")));
+    let Some(source) = source else {
+        return Ok(None);
+    };
+    let path = source
+        .attribute("path")
+        .ok_or_else(|| anyhow::anyhow!("Validator source path missing"))?;
+
+    Ok(Some(ValidatorSpec {
+        language: source_language(source.attribute("type").unwrap_or(path)),
+        source_code: fs::read_to_string(dir.join(path))?,
+    }))
+}
+
+/// 展开 Polygon 路径模板中的 `%d`、`%02d` 等占位符
+fn format_pattern(pattern: &str, index: usize) -> String {
+    let mut result = String::new();
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            result.push(c);
+            continue;
+        }
+
+        let mut width = String::new();
+        while let Some(&d) = chars.peek().filter(|d| d.is_ascii_digit()) {
+            width.push(d);
+            chars.next();
+        }
+        match chars.next() {
+            Some('d') => {
+                let zero_pad = width.starts_with('0');
+                let width: usize = width.parse().unwrap_or(0);
+                if zero_pad {
+                    result.push_str(&format!("{:0width$}", index, width = width));
+                } else {
+                    result.push_str(&format!("{:width$}", index, width = width));
+                }
+            }
+            Some('%') => result.push('%'),
+            Some(other) => {
+                result.push('%');
+                result.push_str(&width);
+                result.push(other);
+            }
+            None => result.push('%'),
+        }
+    }
+    result
+}
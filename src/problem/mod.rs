@@ -0,0 +1,7 @@
+pub mod import;
+pub mod store;
+
+pub use import::{PackageFormat, ProblemPackage};
+pub use store::{
+    ProblemConfig, ProblemManifest, ProblemStore, ProblemUpload, TestCaseEntry, VersionConflict,
+};
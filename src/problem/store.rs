@@ -0,0 +1,443 @@
+use crate::config::settings;
+use crate::judge::{CheckerSpec, Subtask, TestCase, TestData, ValidatorSpec};
+use crate::problem::import::ProblemPackage;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const CURRENT_FILE: &str = "CURRENT";
+
+static UPLOAD_ID: AtomicU64 = AtomicU64::new(0);
+
+/// 题目某一版本测试数据的清单
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProblemManifest {
+    pub problem_id: String,
+    pub version: String,
+    #[serde(flatten)]
+    pub config: ProblemConfig,
+    pub test_cases: Vec<TestCaseEntry>,
+}
+
+/// 题目的评测配置，导入题目包时从包中解析
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProblemConfig {
+    pub name: String,
+    pub time_limit_ms: Option<u64>,
+    pub memory_limit_mb: Option<u64>,
+    pub subtasks: Vec<Subtask>,
+    pub checker: CheckerSpec,
+    pub validator: Option<ValidatorSpec>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestCaseEntry {
+    pub input: String,  // 输入文件名
+    pub output: String, // 答案文件名
+    pub input_size: u64,
+    pub output_size: u64,
+    pub input_sha256: String,
+    pub output_sha256: String,
+}
+
+/// 指定的版本已经存在，且测试数据或配置与这次上传的不同
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionConflict {
+    pub problem_id: String,
+    pub version: String,
+}
+
+impl fmt::Display for VersionConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Problem {} version {} already exists with different content",
+            self.problem_id, self.version
+        )
+    }
+}
+
+impl std::error::Error for VersionConflict {}
+
+/// 本地测试数据仓库
+///
+/// 目录结构为 `<root>/<problem_id>/<version>/`，其中保存 `manifest.json` 和
+/// `<n>.in`/`<n>.out` 数据文件，`<root>/<problem_id>/CURRENT` 记录最新上传的版本。
+pub struct ProblemStore {
+    root: PathBuf,
+    verified: Mutex<HashSet<(String, String)>>, // 已完成哈希校验的版本
+}
+
+impl ProblemStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        let root = root.into();
+        Self {
+            // 数据文件路径会传给特殊评测程序，需要使用绝对路径
+            root: std::path::absolute(&root).unwrap_or(root),
+            verified: Mutex::new(HashSet::new()),
+        }
+    }
+
+    pub fn from_settings() -> Self {
+        Self::new(&settings().problem.data_dir)
+    }
+
+    /// 查询题目当前的数据版本，未上传过时返回 `None`
+    pub fn current_version(&self, problem_id: &str) -> Result<Option<String>> {
+        validate_name(problem_id)?;
+        let path = self.root.join(problem_id).join(CURRENT_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(fs::read_to_string(path)?.trim().to_string()))
+    }
+
+    /// 开始上传一个版本的测试数据，数据写入临时目录，完成后原子地替换
+    ///
+    /// `version` 为空时，完成上传后以数据内容的哈希作为版本号。
+    pub fn begin_upload(&self, problem_id: &str, version: &str) -> Result<ProblemUpload> {
+        validate_name(problem_id)?;
+        if !version.is_empty() {
+            validate_name(version)?;
+        }
+
+        let problem_dir = self.root.join(problem_id);
+        let tmp_dir = problem_dir.join(format!(
+            ".upload-{}-{}",
+            std::process::id(),
+            UPLOAD_ID.fetch_add(1, Ordering::SeqCst)
+        ));
+        fs::create_dir_all(&tmp_dir)?;
+
+        Ok(ProblemUpload {
+            problem_id: problem_id.to_string(),
+            version: version.to_string(),
+            problem_dir,
+            tmp_dir,
+            files: BTreeMap::new(),
+            config: ProblemConfig::default(),
+        })
+    }
+
+    /// 保存导入的题目包
+    pub fn save_package(
+        &self,
+        problem_id: &str,
+        version: &str,
+        package: &ProblemPackage,
+    ) -> Result<ProblemManifest> {
+        let mut upload = self.begin_upload(problem_id, version)?;
+        upload.set_config(package.config.clone());
+
+        for (id, test_case) in package.test_cases.iter().enumerate() {
+            upload.write_data(id, false, &test_case.input)?;
+            upload.write_data(id, true, &test_case.expected_output)?;
+        }
+
+        upload.finish(package.test_cases.len(), &[])
+    }
+
+    /// 题目当前版本的评测配置，题目不存在时返回默认配置
+    pub fn current_config(&self, problem_id: &str) -> Result<ProblemConfig> {
+        match self.current_version(problem_id)? {
+            Some(version) => Ok(self.load(problem_id, Some(&version))?.config),
+            None => Ok(ProblemConfig::default()),
+        }
+    }
+
+    /// 读取清单并检查数据完整性，`version` 为空时使用当前版本
+    pub fn load(&self, problem_id: &str, version: Option<&str>) -> Result<ProblemManifest> {
+        let current = self.current_version(problem_id)?;
+        let version = match version.filter(|v| !v.is_empty()) {
+            Some(version) => version.to_string(),
+            None => current.ok_or_else(|| anyhow::anyhow!("Problem {} not found", problem_id))?,
+        };
+        validate_name(&version)?;
+
+        let dir = self.version_dir(problem_id, &version);
+        let manifest_path = dir.join(MANIFEST_FILE);
+        if !manifest_path.exists() {
+            return Err(anyhow::anyhow!(
+                "Problem {} version {} not found",
+                problem_id,
+                version
+            ));
+        }
+        let manifest: ProblemManifest = serde_json::from_slice(&fs::read(manifest_path)?)?;
+
+        // 每个版本在进程内只做一次完整的哈希校验，之后只比较文件大小
+        let key = (problem_id.to_string(), version.clone());
+        let verified = self.verified.lock().unwrap().contains(&key);
+        for entry in &manifest.test_cases {
+            for (name, size, sha256) in [
+                (&entry.input, entry.input_size, &entry.input_sha256),
+                (&entry.output, entry.output_size, &entry.output_sha256),
+            ] {
+                let path = dir.join(name);
+                if fs::metadata(&path)?.len() != size
+                    || (!verified && &file_sha256(&path)? != sha256)
+                {
+                    return Err(anyhow::anyhow!(
+                        "Test data {} of problem {} is corrupted",
+                        name,
+                        problem_id
+                    ));
+                }
+            }
+        }
+        self.verified.lock().unwrap().insert(key);
+
+        Ok(manifest)
+    }
+
+    pub fn version_dir(&self, problem_id: &str, version: &str) -> PathBuf {
+        self.root.join(problem_id).join(version)
+    }
+
+    /// 生成引用数据文件的测试点，数据不会载入内存
+    pub fn test_cases(&self, manifest: &ProblemManifest) -> Vec<TestCase> {
+        let dir = self.version_dir(&manifest.problem_id, &manifest.version);
+        manifest
+            .test_cases
+            .iter()
+            .map(|entry| TestCase {
+                input: TestData::File(dir.join(&entry.input)),
+                expected_output: TestData::File(dir.join(&entry.output)),
+            })
+            .collect()
+    }
+}
+
+struct UploadFile {
+    file: File,
+    size: u64,
+    hasher: Sha256,
+}
+
+/// 进行中的测试数据上传
+pub struct ProblemUpload {
+    problem_id: String,
+    version: String,
+    problem_dir: PathBuf,
+    tmp_dir: PathBuf,
+    files: BTreeMap<(usize, bool), UploadFile>,
+    config: ProblemConfig,
+}
+
+impl ProblemUpload {
+    pub fn set_config(&mut self, config: ProblemConfig) {
+        self.config = config;
+    }
+
+    pub fn config(&self) -> &ProblemConfig {
+        &self.config
+    }
+
+    /// 已接收的测试数据，用于发布前校验；没有收到分块的文件视为空
+    pub fn test_cases(&self, test_case_count: usize) -> Vec<TestCase> {
+        let data = |id: usize, is_output: bool| {
+            if self.files.contains_key(&(id, is_output)) {
+                TestData::File(self.tmp_dir.join(file_name(id, is_output)))
+            } else {
+                TestData::Inline(Vec::new())
+            }
+        };
+        (0..test_case_count)
+            .map(|id| TestCase {
+                input: data(id, false),
+                expected_output: data(id, true),
+            })
+            .collect()
+    }
+
+    /// 追加一个数据分块，同一文件的分块需按顺序写入
+    pub fn write_chunk(&mut self, test_case_id: usize, is_output: bool, data: &[u8]) -> Result<()> {
+        let file = match self.files.entry((test_case_id, is_output)) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let path = self.tmp_dir.join(file_name(test_case_id, is_output));
+                entry.insert(UploadFile {
+                    file: File::create(path)?,
+                    size: 0,
+                    hasher: Sha256::new(),
+                })
+            }
+        };
+
+        file.file.write_all(data)?;
+        file.hasher.update(data);
+        file.size += data.len() as u64;
+        Ok(())
+    }
+
+    /// 以流的方式写入一个完整的数据文件
+    pub fn write_data(
+        &mut self,
+        test_case_id: usize,
+        is_output: bool,
+        data: &TestData,
+    ) -> Result<()> {
+        let mut reader = data.reader()?;
+        let mut buf = vec![0u8; 64 * 1024];
+        loop {
+            let n = reader.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            self.write_chunk(test_case_id, is_output, &buf[..n])?;
+        }
+        Ok(())
+    }
+
+    /// 写入清单并发布为新版本
+    ///
+    /// `digests` 为客户端提供的每个测试点 (输入, 答案) 的 SHA-256，为空时跳过校验。
+    pub fn finish(
+        mut self,
+        test_case_count: usize,
+        digests: &[(String, String)],
+    ) -> Result<ProblemManifest> {
+        if let Some(&(id, _)) = self.files.keys().find(|(id, _)| *id >= test_case_count) {
+            return Err(anyhow::anyhow!(
+                "Test case {} out of range (count {})",
+                id,
+                test_case_count
+            ));
+        }
+
+        let mut test_cases = Vec::with_capacity(test_case_count);
+        for id in 0..test_case_count {
+            // 没有收到分块的文件视为空文件
+            let (input_size, input_sha256) = self.close_file(id, false)?;
+            let (output_size, output_sha256) = self.close_file(id, true)?;
+
+            if let Some((input_digest, output_digest)) = digests.get(id) {
+                if !input_digest.eq_ignore_ascii_case(&input_sha256)
+                    || !output_digest.eq_ignore_ascii_case(&output_sha256)
+                {
+                    return Err(anyhow::anyhow!("Checksum mismatch for test case {}", id));
+                }
+            }
+
+            test_cases.push(TestCaseEntry {
+                input: file_name(id, false),
+                output: file_name(id, true),
+                input_size,
+                output_size,
+                input_sha256,
+                output_sha256,
+            });
+        }
+
+        if self.version.is_empty() {
+            let mut hasher = Sha256::new();
+            hasher.update(serde_json::to_vec(&self.config)?);
+            for entry in &test_cases {
+                hasher.update(&entry.input_sha256);
+                hasher.update(&entry.output_sha256);
+            }
+            self.version = format!("{:x}", hasher.finalize())[..16].to_string();
+        }
+
+        let manifest = ProblemManifest {
+            problem_id: self.problem_id.clone(),
+            version: self.version.clone(),
+            config: self.config.clone(),
+            test_cases,
+        };
+        fs::write(
+            self.tmp_dir.join(MANIFEST_FILE),
+            serde_json::to_vec_pretty(&manifest)?,
+        )?;
+
+        // 同一版本已存在时内容必须相同，保留旧数据，只更新当前版本指针
+        let version_dir = self.problem_dir.join(&self.version);
+        if fs::rename(&self.tmp_dir, &version_dir).is_err() {
+            let existing = version_dir.join(MANIFEST_FILE);
+            if !existing.exists() {
+                return Err(anyhow::anyhow!("Failed to publish test data"));
+            }
+            let stored: ProblemManifest = serde_json::from_slice(&fs::read(existing)?)?;
+            if stored != manifest {
+                return Err(VersionConflict {
+                    problem_id: self.problem_id.clone(),
+                    version: self.version.clone(),
+                }
+                .into());
+            }
+        }
+        let current_tmp = self.tmp_dir.with_extension("current");
+        fs::write(&current_tmp, &self.version)?;
+        fs::rename(&current_tmp, self.problem_dir.join(CURRENT_FILE))?;
+
+        Ok(manifest)
+    }
+
+    fn close_file(&mut self, test_case_id: usize, is_output: bool) -> Result<(u64, String)> {
+        match self.files.remove(&(test_case_id, is_output)) {
+            Some(mut upload) => {
+                upload.file.flush()?;
+                Ok((upload.size, format!("{:x}", upload.hasher.finalize())))
+            }
+            None => {
+                File::create(self.tmp_dir.join(file_name(test_case_id, is_output)))?;
+                Ok((0, format!("{:x}", Sha256::new().finalize())))
+            }
+        }
+    }
+}
+
+impl Drop for ProblemUpload {
+    fn drop(&mut self) {
+        // 上传中断或失败时清理临时目录
+        if self.tmp_dir.exists() {
+            fs::remove_dir_all(&self.tmp_dir).ok();
+        }
+    }
+}
+
+fn file_name(test_case_id: usize, is_output: bool) -> String {
+    format!(
+        "{}.{}",
+        test_case_id + 1,
+        if is_output { "out" } else { "in" }
+    )
+}
+
+fn file_sha256(path: &Path) -> Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// 题目 ID 和版本号会作为目录名使用，只允许安全字符
+fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= 128
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        return Err(anyhow::anyhow!("Invalid problem id or version: {:?}", name));
+    }
+    Ok(())
+}
@@ -0,0 +1,283 @@
+use crate::config::settings;
+use crate::metrics::metrics;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+
+/// 评测任务的优先级，数值越小越先执行
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    Contest, // 比赛提交
+    #[default]
+    Practice, // 练习提交
+    Run,     // IDE 自定义输入运行
+    Answers, // 题目数据的校验和参考解运行
+    Rejudge, // 重测
+}
+
+/// 排队任务的归属，用于同一优先级内的公平调度
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TaskInfo {
+    pub priority: Priority,
+    pub user_id: String,    // 为空时视为同一个匿名用户
+    pub contest_id: String, // 非比赛任务为空
+}
+
+/// 队列中的任务
+#[derive(Debug, Clone)]
+pub struct QueuedTask {
+    pub task: TaskInfo,
+    pub elapsed: Duration, // 已排队或已执行的时间
+}
+
+/// 队列状态快照
+#[derive(Debug, Clone)]
+pub struct QueueStatus {
+    pub workers: usize,
+    pub running: Vec<QueuedTask>,
+    pub waiting: Vec<QueuedTask>, // 按预计执行顺序排列
+}
+
+struct Waiter {
+    task: TaskInfo,
+    seq: u64,
+    enqueued: Instant,
+    wake: oneshot::Sender<u64>,
+}
+
+#[derive(Default)]
+struct QueueState {
+    next_seq: u64,
+    running: HashMap<u64, (TaskInfo, Instant)>,
+    waiting: Vec<Waiter>,
+    // 各用户、各比赛最近一次被放行时的序号，只保留仍有任务排队的条目
+    user_served: HashMap<String, u64>,
+    contest_served: HashMap<String, u64>,
+    dispatched: u64,
+}
+
+/// 评测队列
+///
+/// 限制同时执行的任务数。有空位时先按优先级选择；同一优先级内选择最久未被放行的
+/// 比赛，再选择其中最久未被放行的用户，最后按到达顺序，避免单个用户大量提交
+/// 占满队列。
+#[derive(Clone)]
+pub struct JudgeQueue {
+    workers: usize,
+    state: Arc<Mutex<QueueState>>,
+}
+
+/// 执行许可，销毁时把空位交给下一个等待的任务
+pub struct Permit {
+    id: u64,
+    state: Arc<Mutex<QueueState>>,
+    workers: usize,
+}
+
+impl JudgeQueue {
+    pub fn new(workers: usize) -> Self {
+        let workers = workers.max(1);
+        metrics().set_queue(0, 0, workers);
+        Self {
+            workers,
+            state: Default::default(),
+        }
+    }
+
+    pub fn from_settings() -> Self {
+        Self::new(settings().queue.workers)
+    }
+
+    /// 等待执行许可；等待中的 future 被取消时自动让出位置
+    pub async fn acquire(&self, task: TaskInfo) -> Permit {
+        let receiver = {
+            let mut state = self.state.lock().unwrap();
+            let seq = state.next_seq;
+            state.next_seq += 1;
+            if state.running.len() < self.workers && state.waiting.is_empty() {
+                state.mark_served(&task);
+                state.running.insert(seq, (task, Instant::now()));
+                state.update_metrics(self.workers);
+                return self.permit(seq);
+            }
+            let (wake, receiver) = oneshot::channel();
+            state.waiting.push(Waiter {
+                task,
+                seq,
+                enqueued: Instant::now(),
+                wake,
+            });
+            state.update_metrics(self.workers);
+            receiver
+        };
+
+        let guard = WaitGuard {
+            queue: self,
+            receiver: Some(receiver),
+        };
+        let id = guard.wait().await;
+        self.permit(id)
+    }
+
+    /// 同时执行的任务数上限
+    pub fn workers(&self) -> usize {
+        self.workers
+    }
+
+    /// 等待中的任务数
+    pub fn waiting(&self) -> usize {
+        self.state.lock().unwrap().waiting.len()
+    }
+
+    pub fn status(&self) -> QueueStatus {
+        let state = self.state.lock().unwrap();
+        let now = Instant::now();
+
+        let mut running: Vec<_> = state.running.iter().collect();
+        running.sort_by_key(|(seq, _)| **seq);
+        let running = running
+            .into_iter()
+            .map(|(_, (task, started))| QueuedTask {
+                task: task.clone(),
+                elapsed: now - *started,
+            })
+            .collect();
+
+        // 在副本上模拟放行过程得到预计执行顺序
+        let mut user_served = state.user_served.clone();
+        let mut contest_served = state.contest_served.clone();
+        let mut dispatched = state.dispatched;
+        let mut pending: Vec<&Waiter> = state.waiting.iter().collect();
+        let mut waiting = Vec::with_capacity(pending.len());
+        while let Some(index) = next_waiter(&pending, &user_served, &contest_served) {
+            let waiter = pending.swap_remove(index);
+            dispatched += 1;
+            user_served.insert(waiter.task.user_id.clone(), dispatched);
+            contest_served.insert(waiter.task.contest_id.clone(), dispatched);
+            waiting.push(QueuedTask {
+                task: waiter.task.clone(),
+                elapsed: now - waiter.enqueued,
+            });
+        }
+
+        QueueStatus {
+            workers: self.workers,
+            running,
+            waiting,
+        }
+    }
+
+    fn permit(&self, id: u64) -> Permit {
+        Permit {
+            id,
+            state: self.state.clone(),
+            workers: self.workers,
+        }
+    }
+}
+
+impl QueueState {
+    fn update_metrics(&self, workers: usize) {
+        metrics().set_queue(self.waiting.len(), self.running.len(), workers);
+    }
+
+    fn mark_served(&mut self, task: &TaskInfo) {
+        self.dispatched += 1;
+        self.user_served
+            .insert(task.user_id.clone(), self.dispatched);
+        self.contest_served
+            .insert(task.contest_id.clone(), self.dispatched);
+    }
+
+    /// 放行等待的任务直到没有空位
+    fn dispatch(&mut self, workers: usize) {
+        while self.running.len() < workers {
+            let index = {
+                let waiting: Vec<&Waiter> = self.waiting.iter().collect();
+                next_waiter(&waiting, &self.user_served, &self.contest_served)
+            };
+            let Some(index) = index else {
+                break;
+            };
+            let waiter = self.waiting.swap_remove(index);
+            // 接收方已取消等待时继续放行下一个
+            if waiter.wake.send(waiter.seq).is_ok() {
+                self.mark_served(&waiter.task);
+                self.running
+                    .insert(waiter.seq, (waiter.task, Instant::now()));
+            }
+        }
+
+        // 没有任务排队的用户和比赛不再需要记录
+        let waiting = &self.waiting;
+        self.user_served
+            .retain(|user, _| waiting.iter().any(|w| &w.task.user_id == user));
+        self.contest_served
+            .retain(|contest, _| waiting.iter().any(|w| &w.task.contest_id == contest));
+        self.update_metrics(workers);
+    }
+}
+
+/// 选择下一个放行的任务：优先级、比赛、用户、到达顺序
+fn next_waiter(
+    waiting: &[&Waiter],
+    user_served: &HashMap<String, u64>,
+    contest_served: &HashMap<String, u64>,
+) -> Option<usize> {
+    waiting
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, w)| {
+            (
+                w.task.priority,
+                contest_served.get(&w.task.contest_id).copied().unwrap_or(0),
+                user_served.get(&w.task.user_id).copied().unwrap_or(0),
+                w.seq,
+            )
+        })
+        .map(|(index, _)| index)
+}
+
+/// 等待中的任务被取消时，清理队列中的记录；已被放行则归还空位
+struct WaitGuard<'a> {
+    queue: &'a JudgeQueue,
+    receiver: Option<oneshot::Receiver<u64>>,
+}
+
+impl WaitGuard<'_> {
+    async fn wait(mut self) -> u64 {
+        let Some(receiver) = self.receiver.as_mut() else {
+            return 0;
+        };
+        // 发送方只会在转交空位后才发送，不会被提前丢弃
+        let id = receiver.await.unwrap_or_default();
+        self.receiver = None;
+        id
+    }
+}
+
+impl Drop for WaitGuard<'_> {
+    fn drop(&mut self) {
+        let Some(mut receiver) = self.receiver.take() else {
+            return;
+        };
+        receiver.close();
+        {
+            let mut state = self.queue.state.lock().unwrap();
+            state.waiting.retain(|w| !w.wake.is_closed());
+            state.update_metrics(self.queue.workers);
+        }
+        if let Ok(id) = receiver.try_recv() {
+            drop(self.queue.permit(id));
+        }
+    }
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        let mut state = self.state.lock().unwrap();
+        state.running.remove(&self.id);
+        state.dispatch(self.workers);
+    }
+}
@@ -0,0 +1,461 @@
+use crate::config::settings;
+use crate::judge::{JudgeConfig, JudgeResult, JudgeStatus, TestCase, TestCaseResult, TestData};
+use anyhow::Result;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS submissions (
+    id TEXT PRIMARY KEY,
+    language TEXT NOT NULL,
+    source_code TEXT NOT NULL,
+    time_limit_ms INTEGER NOT NULL,
+    memory_limit INTEGER NOT NULL,
+    problem_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    contest_id TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS submissions_problem ON submissions (problem_id, created_at);
+CREATE TABLE IF NOT EXISTS submission_test_cases (
+    submission_id TEXT NOT NULL,
+    test_case_id INTEGER NOT NULL,
+    input BLOB NOT NULL,
+    expected_output BLOB NOT NULL,
+    PRIMARY KEY (submission_id, test_case_id)
+);
+CREATE TABLE IF NOT EXISTS runs (
+    submission_id TEXT NOT NULL,
+    run INTEGER NOT NULL,
+    problem_version TEXT NOT NULL,
+    status TEXT NOT NULL,
+    time_used_us INTEGER NOT NULL,
+    memory_used INTEGER NOT NULL,
+    error_message TEXT,
+    judged_at INTEGER NOT NULL,
+    PRIMARY KEY (submission_id, run)
+);
+CREATE TABLE IF NOT EXISTS run_test_cases (
+    submission_id TEXT NOT NULL,
+    run INTEGER NOT NULL,
+    test_case_id INTEGER NOT NULL,
+    status TEXT NOT NULL,
+    time_used_us INTEGER NOT NULL,
+    memory_used INTEGER NOT NULL,
+    actual_output BLOB NOT NULL,
+    PRIMARY KEY (submission_id, run, test_case_id)
+);
+";
+
+/// 一次提交的原始请求，重测时据此重新评测
+#[derive(Debug, Clone)]
+pub struct Submission {
+    pub id: String,
+    pub config: JudgeConfig,
+    pub problem_id: String,        // 为空时使用 `test_cases`
+    pub test_cases: Vec<TestCase>, // 提交中附带的测试点，引用题目数据时为空
+    pub user_id: String,
+    pub contest_id: String,
+    pub created_at: u64, // Unix 时间戳（秒）
+}
+
+/// 一次评测的结果，首次评测为第 1 次，之后每次重测加 1
+#[derive(Debug, Clone)]
+pub struct JudgeRun {
+    pub run: u32,
+    pub problem_version: String, // 使用提交中附带的测试点时为空
+    pub result: JudgeResult,
+    pub judged_at: u64,
+}
+
+/// 两次评测结果的差异
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResultDiff {
+    pub previous_status: Option<JudgeStatus>, // 没有上一次结果时为 None
+    pub status: JudgeStatus,
+    pub changes: Vec<TestCaseChange>, // 结果发生变化的测试点
+}
+
+/// 单个测试点的结果变化，某次评测没有该测试点时对应的状态为 None
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestCaseChange {
+    pub test_case_id: usize,
+    pub previous_status: Option<JudgeStatus>,
+    pub status: Option<JudgeStatus>,
+}
+
+impl ResultDiff {
+    pub fn new(previous: Option<&JudgeResult>, current: &JudgeResult) -> Self {
+        let status_of = |result: Option<&JudgeResult>, id: usize| {
+            result?
+                .test_case_results
+                .iter()
+                .find(|r| r.test_case_id == id)
+                .map(|r| r.status.clone())
+        };
+
+        let count = previous
+            .map(|p| p.test_case_results.len())
+            .unwrap_or(0)
+            .max(current.test_case_results.len());
+        let changes = (0..count)
+            .filter_map(|id| {
+                let previous_status = status_of(previous, id);
+                let status = status_of(Some(current), id);
+                (previous.is_some() && previous_status != status).then_some(TestCaseChange {
+                    test_case_id: id,
+                    previous_status,
+                    status,
+                })
+            })
+            .collect();
+
+        Self {
+            previous_status: previous.map(|p| p.status.clone()),
+            status: current.status.clone(),
+            changes,
+        }
+    }
+
+    pub fn changed(&self) -> bool {
+        self.previous_status.as_ref() != Some(&self.status) || !self.changes.is_empty()
+    }
+}
+
+/// 批量重测中单个提交的结果
+#[derive(Debug, Clone)]
+pub struct RejudgeEntry {
+    pub submission_id: String,
+    pub run: u32,
+    pub diff: ResultDiff,
+    pub previous_score: Option<f64>, // 没有上一次结果时为 None
+    pub score: f64,
+}
+
+impl RejudgeEntry {
+    pub fn score_delta(&self) -> f64 {
+        self.score - self.previous_score.unwrap_or(0.0)
+    }
+}
+
+/// 某种结果变化（如 Accepted → WrongAnswer）涉及的提交数
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerdictTransition {
+    pub previous_status: JudgeStatus,
+    pub status: JudgeStatus,
+    pub count: usize,
+}
+
+/// 批量重测报告，汇总结果变化和得分变化
+#[derive(Debug, Clone, Default)]
+pub struct RejudgeReport {
+    pub entries: Vec<RejudgeEntry>,
+    pub failures: Vec<(String, String)>, // 重测本身失败的提交 ID 和原因
+}
+
+impl RejudgeReport {
+    /// 最终结果发生变化的提交，按变化类型分组计数，顺序为首次出现的顺序
+    pub fn transitions(&self) -> Vec<VerdictTransition> {
+        let mut transitions: Vec<VerdictTransition> = Vec::new();
+        for entry in &self.entries {
+            let Some(previous) = &entry.diff.previous_status else {
+                continue;
+            };
+            if *previous == entry.diff.status {
+                continue;
+            }
+            match transitions
+                .iter_mut()
+                .find(|t| t.previous_status == *previous && t.status == entry.diff.status)
+            {
+                Some(transition) => transition.count += 1,
+                None => transitions.push(VerdictTransition {
+                    previous_status: previous.clone(),
+                    status: entry.diff.status.clone(),
+                    count: 1,
+                }),
+            }
+        }
+        transitions
+    }
+
+    /// 结果或得分发生变化的提交
+    pub fn affected(&self) -> impl Iterator<Item = &RejudgeEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.diff.changed() || entry.score_delta() != 0.0)
+    }
+}
+
+/// 提交与评测结果的持久化存储（SQLite）
+///
+/// 提交在评测前写入，结果在评测完成后写入，调用方断开连接后仍可查询。
+pub struct SubmissionStore {
+    conn: Mutex<Connection>,
+}
+
+impl SubmissionStore {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(path)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    pub fn from_settings() -> Result<Self> {
+        Self::open(&settings().store.path)
+    }
+
+    /// 保存新提交，ID 已存在时返回 `false` 且不做修改
+    pub fn create(&self, submission: &Submission) -> Result<bool> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let inserted = tx.execute(
+            "INSERT OR IGNORE INTO submissions
+             (id, language, source_code, time_limit_ms, memory_limit, problem_id, user_id, contest_id, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                submission.id,
+                submission.config.language,
+                submission.config.source_code,
+                submission.config.time_limit.as_millis() as i64,
+                submission.config.memory_limit as i64,
+                submission.problem_id,
+                submission.user_id,
+                submission.contest_id,
+                submission.created_at as i64,
+            ],
+        )?;
+        if inserted == 0 {
+            return Ok(false);
+        }
+
+        for (id, test_case) in submission.test_cases.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO submission_test_cases (submission_id, test_case_id, input, expected_output)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    submission.id,
+                    id as i64,
+                    read_data(&test_case.input)?,
+                    read_data(&test_case.expected_output)?,
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(true)
+    }
+
+    pub fn get(&self, id: &str) -> Result<Option<Submission>> {
+        let conn = self.conn.lock().unwrap();
+        let submission = conn
+            .query_row(
+                "SELECT language, source_code, time_limit_ms, memory_limit, problem_id, user_id, contest_id, created_at
+                 FROM submissions WHERE id = ?1",
+                params![id],
+                |row| {
+                    Ok(Submission {
+                        id: id.to_string(),
+                        config: JudgeConfig {
+                            language: row.get(0)?,
+                            source_code: row.get(1)?,
+                            time_limit: Duration::from_millis(row.get::<_, i64>(2)? as u64),
+                            memory_limit: row.get::<_, i64>(3)? as u64,
+                        },
+                        problem_id: row.get(4)?,
+                        test_cases: Vec::new(),
+                        user_id: row.get(5)?,
+                        contest_id: row.get(6)?,
+                        created_at: row.get::<_, i64>(7)? as u64,
+                    })
+                },
+            )
+            .optional()?;
+        let Some(mut submission) = submission else {
+            return Ok(None);
+        };
+
+        let mut stmt = conn.prepare(
+            "SELECT input, expected_output FROM submission_test_cases
+             WHERE submission_id = ?1 ORDER BY test_case_id",
+        )?;
+        submission.test_cases = stmt
+            .query_map(params![id], |row| {
+                Ok(TestCase {
+                    input: TestData::Inline(row.get(0)?),
+                    expected_output: TestData::Inline(row.get(1)?),
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+
+        Ok(Some(submission))
+    }
+
+    /// 引用指定题目的所有提交 ID，按提交时间排序
+    pub fn submissions_of_problem(&self, problem_id: &str) -> Result<Vec<String>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id FROM submissions WHERE problem_id = ?1 ORDER BY created_at, rowid",
+        )?;
+        let ids = stmt
+            .query_map(params![problem_id], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(ids)
+    }
+
+    /// 没有任何评测结果的提交 ID，按提交时间排序
+    ///
+    /// 评测中途进程退出（如崩溃或被强制结束）时，提交已保存但结果没有记录。
+    pub fn unjudged_submissions(&self) -> Result<Vec<String>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id FROM submissions WHERE NOT EXISTS \
+             (SELECT 1 FROM runs WHERE runs.submission_id = submissions.id) \
+             ORDER BY created_at, rowid",
+        )?;
+        let ids = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(ids)
+    }
+
+    /// 记录一次评测结果，返回评测序号
+    pub fn record_run(
+        &self,
+        submission_id: &str,
+        problem_version: &str,
+        result: &JudgeResult,
+    ) -> Result<u32> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let run: u32 = tx.query_row(
+            "SELECT COALESCE(MAX(run), 0) + 1 FROM runs WHERE submission_id = ?1",
+            params![submission_id],
+            |row| row.get(0),
+        )?;
+        tx.execute(
+            "INSERT INTO runs
+             (submission_id, run, problem_version, status, time_used_us, memory_used, error_message, judged_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                submission_id,
+                run,
+                problem_version,
+                status_name(&result.status),
+                result.time_used.as_micros() as i64,
+                result.memory_used as i64,
+                result.error_message,
+                now() as i64,
+            ],
+        )?;
+        for test_case in &result.test_case_results {
+            tx.execute(
+                "INSERT INTO run_test_cases
+                 (submission_id, run, test_case_id, status, time_used_us, memory_used, actual_output)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    submission_id,
+                    run,
+                    test_case.test_case_id as i64,
+                    status_name(&test_case.status),
+                    test_case.time_used.as_micros() as i64,
+                    test_case.memory_used as i64,
+                    test_case.actual_output,
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(run)
+    }
+
+    /// 提交的所有评测结果，按评测序号排序
+    pub fn runs(&self, submission_id: &str) -> Result<Vec<JudgeRun>> {
+        self.runs_from(submission_id, 0)
+    }
+
+    /// 提交最近一次的评测结果
+    pub fn last_run(&self, submission_id: &str) -> Result<Option<JudgeRun>> {
+        Ok(self.runs_from(submission_id, u32::MAX)?.pop())
+    }
+
+    /// 评测序号不小于 `from` 的结果，`from` 为 `u32::MAX` 时只取最后一次
+    fn runs_from(&self, submission_id: &str, from: u32) -> Result<Vec<JudgeRun>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT run, problem_version, status, time_used_us, memory_used, error_message, judged_at
+             FROM runs WHERE submission_id = ?1
+             AND run >= MIN(?2, (SELECT COALESCE(MAX(run), 0) FROM runs WHERE submission_id = ?1))
+             ORDER BY run",
+        )?;
+        let mut runs: Vec<JudgeRun> = stmt
+            .query_map(params![submission_id, from], |row| {
+                Ok(JudgeRun {
+                    run: row.get(0)?,
+                    problem_version: row.get(1)?,
+                    result: JudgeResult {
+                        status: parse_status(row.get(2)?),
+                        time_used: Duration::from_micros(row.get::<_, i64>(3)? as u64),
+                        memory_used: row.get::<_, i64>(4)? as u64,
+                        error_message: row.get(5)?,
+                        test_case_results: Vec::new(),
+                    },
+                    judged_at: row.get::<_, i64>(6)? as u64,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT test_case_id, status, time_used_us, memory_used, actual_output
+             FROM run_test_cases WHERE submission_id = ?1 AND run = ?2 ORDER BY test_case_id",
+        )?;
+        for run in &mut runs {
+            run.result.test_case_results = stmt
+                .query_map(params![submission_id, run.run], |row| {
+                    Ok(TestCaseResult {
+                        test_case_id: row.get::<_, i64>(0)? as usize,
+                        status: parse_status(row.get(1)?),
+                        time_used: Duration::from_micros(row.get::<_, i64>(2)? as u64),
+                        memory_used: row.get::<_, i64>(3)? as u64,
+                        actual_output: row.get(4)?,
+                        output_diff: None,
+                    })
+                })?
+                .collect::<rusqlite::Result<_>>()?;
+        }
+
+        Ok(runs)
+    }
+}
+
+/// 当前 Unix 时间戳（秒）
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn read_data(data: &TestData) -> Result<Vec<u8>> {
+    let mut content = Vec::new();
+    data.reader()?.read_to_end(&mut content)?;
+    Ok(content)
+}
+
+fn status_name(status: &JudgeStatus) -> String {
+    format!("{:?}", status)
+}
+
+/// 无法识别的状态（例如由更新版本写入）按系统错误处理
+fn parse_status(name: String) -> JudgeStatus {
+    serde_json::from_value(serde_json::Value::String(name)).unwrap_or(JudgeStatus::SystemError)
+}
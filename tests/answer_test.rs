@@ -0,0 +1,70 @@
+use opti_neko::judge::{AnswerGenerator, JudgeConfig, JudgeStatus, TestData};
+use opti_neko::problem::ProblemStore;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("opti-neko-test-{}-{}", name, std::process::id()));
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn reference(source_code: &str) -> JudgeConfig {
+    JudgeConfig {
+        time_limit: Duration::from_secs(1),
+        memory_limit: 256 * 1024 * 1024,
+        language: "python3".to_string(),
+        source_code: source_code.to_string(),
+    }
+}
+
+#[tokio::test]
+async fn test_generate_answers_into_store() {
+    let root = temp_dir("answers");
+    let store = ProblemStore::new(&root);
+    let inputs: Vec<TestData> = vec!["1 2\n".into(), "10 20\n".into()];
+
+    let mut upload = store.begin_upload("2001", "").unwrap();
+    for (id, input) in inputs.iter().enumerate() {
+        upload.write_data(id, false, input).unwrap();
+    }
+    let runs = AnswerGenerator::new(reference(
+        "a, b = map(int, input().split())\nprint(a + b)\n",
+    ))
+    .generate(&inputs, |id, output| {
+        upload.write_data(id, true, &TestData::File(output.to_path_buf()))
+    })
+    .await
+    .unwrap();
+    assert!(runs.iter().all(|run| run.status == JudgeStatus::Accepted));
+
+    let manifest = upload.finish(inputs.len(), &[]).unwrap();
+    let dir = store.version_dir("2001", &manifest.version);
+    assert_eq!(fs::read_to_string(dir.join("1.out")).unwrap(), "3\n");
+    assert_eq!(fs::read_to_string(dir.join("2.out")).unwrap(), "30\n");
+
+    fs::remove_dir_all(root).ok();
+}
+
+#[tokio::test]
+async fn test_reference_failures_are_flagged() {
+    let inputs: Vec<TestData> = vec!["1\n".into(), "0\n".into(), "-1\n".into()];
+    let mut outputs = Vec::new();
+
+    let runs = AnswerGenerator::new(reference(
+        "import time\nn = int(input())\nif n < 0:\n    time.sleep(5)\nprint(1 // n)\n",
+    ))
+    .generate(&inputs, |id, _| {
+        outputs.push(id);
+        Ok(())
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(runs[0].status, JudgeStatus::Accepted);
+    assert_eq!(runs[1].status, JudgeStatus::RuntimeError);
+    assert_eq!(runs[2].status, JudgeStatus::TimeLimitExceeded);
+    assert_eq!(outputs, vec![0]);
+}
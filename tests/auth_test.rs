@@ -0,0 +1,185 @@
+use opti_neko::auth::{self, Authenticator, QuotaError};
+use opti_neko::config::{AuthSettings, ClientSettings};
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn authenticator() -> Authenticator {
+    Authenticator::new(&AuthSettings {
+        enabled: true,
+        max_clock_skew_secs: 60,
+        clients: vec![
+            ClientSettings {
+                id: "backend".to_string(),
+                token: Some("backend-token".to_string()),
+                hmac_secret: Some("backend-secret".to_string()),
+                max_concurrent: 1,
+                max_total_time_ms: 10_000,
+                max_test_cases: 5,
+            },
+            ClientSettings {
+                id: "contest".to_string(),
+                token: Some("contest-token".to_string()),
+                ..Default::default()
+            },
+        ],
+    })
+}
+
+fn headers(pairs: &[(&str, String)]) -> impl Fn(&str) -> Option<String> {
+    let map: HashMap<String, String> = pairs
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.clone()))
+        .collect();
+    move |name| map.get(name).cloned()
+}
+
+const SUBMIT: &str = "/judge_grpc_service.JudgeGrpcService/Submit";
+const REJUDGE: &str = "/judge_grpc_service.JudgeGrpcService/Rejudge";
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[test]
+fn test_bearer_token() {
+    let auth = authenticator();
+
+    let client = auth
+        .authenticate(
+            SUBMIT,
+            headers(&[("authorization", "Bearer contest-token".to_string())]),
+        )
+        .unwrap();
+    assert_eq!(client.id(), "contest");
+
+    assert!(auth
+        .authenticate(
+            SUBMIT,
+            headers(&[("authorization", "Bearer wrong".to_string())])
+        )
+        .is_err());
+    assert!(auth
+        .authenticate(
+            SUBMIT,
+            headers(&[("authorization", "Basic abc".to_string())])
+        )
+        .is_err());
+    assert!(auth.authenticate(SUBMIT, headers(&[])).is_err());
+}
+
+#[test]
+fn test_hmac_signature() {
+    let auth = authenticator();
+    let signed = |client: &str, timestamp: u64, nonce: &str, secret: &str| {
+        headers(&[
+            (auth::CLIENT_HEADER, client.to_string()),
+            (auth::TIMESTAMP_HEADER, timestamp.to_string()),
+            (auth::NONCE_HEADER, nonce.to_string()),
+            (
+                auth::SIGNATURE_HEADER,
+                auth::sign(secret, client, timestamp, nonce, SUBMIT),
+            ),
+        ])
+    };
+
+    let client = auth
+        .authenticate(SUBMIT, signed("backend", now(), "n1", "backend-secret"))
+        .unwrap();
+    assert_eq!(client.id(), "backend");
+
+    // 密钥错误、签名过期、客户端未配置密钥都被拒绝
+    assert!(auth
+        .authenticate(SUBMIT, signed("backend", now(), "n2", "wrong"))
+        .is_err());
+    assert!(auth
+        .authenticate(
+            SUBMIT,
+            signed("backend", now() - 120, "n3", "backend-secret")
+        )
+        .is_err());
+    assert!(auth
+        .authenticate(SUBMIT, signed("contest", now(), "n4", "backend-secret"))
+        .is_err());
+}
+
+#[test]
+fn test_hmac_signature_cannot_be_replayed() {
+    let auth = authenticator();
+    let timestamp = now();
+    let signature = auth::sign("backend-secret", "backend", timestamp, "n1", SUBMIT);
+    let captured = || {
+        headers(&[
+            (auth::CLIENT_HEADER, "backend".to_string()),
+            (auth::TIMESTAMP_HEADER, timestamp.to_string()),
+            (auth::NONCE_HEADER, "n1".to_string()),
+            (auth::SIGNATURE_HEADER, signature.clone()),
+        ])
+    };
+
+    // 签名绑定方法路径，不能用于其他接口
+    assert!(auth.authenticate(REJUDGE, captured()).is_err());
+    assert!(auth.authenticate(SUBMIT, captured()).is_ok());
+    // 同一 nonce 只能使用一次
+    assert!(auth.authenticate(SUBMIT, captured()).is_err());
+
+    // 缺少 nonce 的请求被拒绝
+    assert!(auth
+        .authenticate(
+            SUBMIT,
+            headers(&[
+                (auth::CLIENT_HEADER, "backend".to_string()),
+                (auth::TIMESTAMP_HEADER, timestamp.to_string()),
+                (auth::SIGNATURE_HEADER, signature.clone()),
+            ])
+        )
+        .is_err());
+}
+
+#[test]
+fn test_quota() {
+    let auth = authenticator();
+    let client = auth
+        .authenticate(
+            SUBMIT,
+            headers(&[("authorization", "Bearer backend-token".to_string())]),
+        )
+        .unwrap();
+
+    assert!(matches!(
+        client.begin(6, 1000),
+        Err(QuotaError::PermissionDenied(_))
+    ));
+    assert!(matches!(
+        client.begin(5, 3000),
+        Err(QuotaError::PermissionDenied(_))
+    ));
+
+    let permit = client.begin(5, 2000).unwrap();
+    assert!(matches!(
+        client.begin(1, 1000),
+        Err(QuotaError::ResourceExhausted(_))
+    ));
+    drop(permit);
+    assert!(client.begin(1, 1000).is_ok());
+
+    // 规模检查不占用并发名额
+    assert!(client.check_limits(5, 2000).is_ok());
+    assert!(matches!(
+        client.check_limits(6, 1000),
+        Err(QuotaError::PermissionDenied(_))
+    ));
+
+    // 配额为 0 表示不限制
+    let unlimited = auth
+        .authenticate(
+            SUBMIT,
+            headers(&[("authorization", "Bearer contest-token".to_string())]),
+        )
+        .unwrap();
+    let _permits: Vec<_> = (0..10)
+        .map(|_| unlimited.begin(1000, 10_000).unwrap())
+        .collect();
+}
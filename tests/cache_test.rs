@@ -0,0 +1,60 @@
+use opti_neko::judge::cache::ArtifactCache;
+use std::fs;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("opti-neko-test-{}-{}", name, std::process::id()));
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_cache_restore_hit() {
+    let cache_dir = temp_dir("cache-hit");
+    let work_dir = temp_dir("cache-hit-work");
+    fs::write(work_dir.join("solution.cpp"), "int main() {}").unwrap();
+    fs::write(work_dir.join("solution"), "binary").unwrap();
+
+    let cache = ArtifactCache::new(&cache_dir, 1024 * 1024);
+    assert!(!cache.restore("key", &work_dir).unwrap());
+    cache
+        .store("key", &work_dir, &["solution.cpp".to_string()])
+        .unwrap();
+
+    let restore_dir = temp_dir("cache-hit-restore");
+    assert!(cache.restore("key", &restore_dir).unwrap());
+    assert_eq!(
+        fs::read_to_string(restore_dir.join("solution")).unwrap(),
+        "binary"
+    );
+    assert!(!restore_dir.join("solution.cpp").exists());
+
+    for dir in [cache_dir, work_dir, restore_dir] {
+        fs::remove_dir_all(dir).ok();
+    }
+}
+
+#[test]
+fn test_cache_evicts_least_recently_used() {
+    let cache_dir = temp_dir("cache-lru");
+    let work_dir = temp_dir("cache-lru-work");
+    fs::write(work_dir.join("solution"), vec![0u8; 600]).unwrap();
+
+    let cache = ArtifactCache::new(&cache_dir, 1000);
+    cache
+        .store("old", &work_dir, &["solution.cpp".to_string()])
+        .unwrap();
+    thread::sleep(Duration::from_millis(20));
+    cache
+        .store("new", &work_dir, &["solution.cpp".to_string()])
+        .unwrap();
+
+    assert!(!cache_dir.join("old").exists());
+    assert!(cache_dir.join("new").exists());
+
+    fs::remove_dir_all(cache_dir).ok();
+    fs::remove_dir_all(work_dir).ok();
+}
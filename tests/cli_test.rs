@@ -0,0 +1,98 @@
+use opti_neko::judge::ValidatorSpec;
+use opti_neko::problem::{ProblemConfig, ProblemStore};
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn test_judge_command_json_output() {
+    let dir = std::env::temp_dir().join(format!("opti-neko-test-cli-judge-{}", std::process::id()));
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(dir.join("tests")).unwrap();
+    fs::write(dir.join("tests/1.in"), "1 2\n").unwrap();
+    fs::write(dir.join("tests/1.out"), "3\n").unwrap();
+    fs::write(dir.join("tests/2.in"), "2 2\n").unwrap();
+    fs::write(dir.join("tests/2.out"), "5\n").unwrap();
+    fs::write(
+        dir.join("solution.py"),
+        "a, b = map(int, input().split())\nprint(a + b)\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_opti-neko"))
+        .args(["judge", "--language", "python3", "--json"])
+        .arg(dir.join("solution.py"))
+        .arg(dir.join("tests"))
+        .output()
+        .unwrap();
+
+    // 存在未通过的测试点时以非零状态退出
+    assert_eq!(output.status.code(), Some(1));
+
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["status"], "WrongAnswer");
+    assert_eq!(result["test_cases"][0]["name"], "1");
+    assert_eq!(result["test_cases"][0]["status"], "Accepted");
+    assert_eq!(result["test_cases"][1]["status"], "WrongAnswer");
+    assert_eq!(result["test_cases"][1]["output_diff"]["line"], 1);
+    assert_eq!(
+        result["test_cases"][0]["output_diff"],
+        serde_json::Value::Null
+    );
+
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn test_invalid_config_is_fatal() {
+    // 配置有误时拒绝启动，不能退回关闭认证和 TLS 的默认配置
+    let output = Command::new(env!("CARGO_BIN_EXE_opti-neko"))
+        .arg("serve")
+        .env("NEKO_AUTH__ENABLED", "maybe")
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("auth.enabled"));
+}
+
+#[test]
+fn test_answers_command_validates_inputs() {
+    let dir =
+        std::env::temp_dir().join(format!("opti-neko-test-cli-answers-{}", std::process::id()));
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(dir.join("inputs")).unwrap();
+
+    // 题目配置了只接受 "ok" 的校验程序
+    let store = ProblemStore::new(dir.join("data"));
+    let mut upload = store.begin_upload("3001", "v1").unwrap();
+    upload.set_config(ProblemConfig {
+        validator: Some(ValidatorSpec {
+            language: "python3".to_string(),
+            source_code: "import sys\nif input() != 'ok':\n    sys.exit(1)\n".to_string(),
+        }),
+        ..Default::default()
+    });
+    upload.write_chunk(0, false, b"ok\n").unwrap();
+    upload.finish(1, &[]).unwrap();
+
+    fs::write(dir.join("inputs/1.in"), "ok\n").unwrap();
+    fs::write(dir.join("inputs/2.in"), "bad\n").unwrap();
+    fs::write(dir.join("solution.py"), "print(input())\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_opti-neko"))
+        .args(["answers", "--problem-id", "3001", "--reference"])
+        .arg(dir.join("solution.py"))
+        .arg(dir.join("inputs"))
+        .env("NEKO_PROBLEM__DATA_DIR", dir.join("data"))
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("1 test case(s) failed validation"));
+    assert_eq!(
+        store.current_version("3001").unwrap().as_deref(),
+        Some("v1")
+    );
+
+    fs::remove_dir_all(dir).ok();
+}
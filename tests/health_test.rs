@@ -0,0 +1,17 @@
+use opti_neko::judge::health;
+use opti_neko::judge::language::Language;
+
+#[test]
+fn test_toolchain_programs() {
+    let toolchain = |name: &str| Language::resolve(name).unwrap().toolchain();
+    assert_eq!(toolchain("cpp17"), vec!["g++"]);
+    assert_eq!(toolchain("python3"), vec!["python3"]);
+    assert_eq!(toolchain("java17"), vec!["javac", "java"]);
+    assert_eq!(toolchain("typescript"), vec!["tsc", "node"]);
+}
+
+#[tokio::test]
+async fn test_health_check_passes() {
+    // 默认配置要求 cpp17 和 python3 的工具链可用
+    health::check().await.unwrap();
+}
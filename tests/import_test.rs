@@ -0,0 +1,164 @@
+use opti_neko::judge::{CheckerSpec, TestData};
+use opti_neko::problem::import::import_package;
+use opti_neko::problem::{PackageFormat, ProblemStore};
+use std::fs;
+use std::path::PathBuf;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("opti-neko-test-{}-{}", name, std::process::id()));
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_import_hustoj_directory() {
+    let dir = temp_dir("import-hustoj");
+    let package_dir = dir.join("package");
+    fs::create_dir_all(&package_dir).unwrap();
+    for (name, input, output) in [
+        ("1", "1 2\n", "3\n"),
+        ("10", "5 5\n", "10\n"),
+        ("2", "2 2\n", "4\n"),
+    ] {
+        fs::write(package_dir.join(format!("{}.in", name)), input).unwrap();
+        fs::write(package_dir.join(format!("{}.out", name)), output).unwrap();
+    }
+
+    let package = import_package(PackageFormat::Hustoj, &package_dir).unwrap();
+    assert_eq!(package.test_cases.len(), 3);
+    assert_eq!(package.config.checker, CheckerSpec::Exact);
+
+    let store = ProblemStore::new(dir.join("store"));
+    let manifest = store.save_package("1001", "", &package).unwrap();
+    assert_eq!(manifest.version.len(), 16);
+    // 按自然顺序排列：1、2、10
+    assert_eq!(
+        fs::read_to_string(store.version_dir("1001", &manifest.version).join("3.out")).unwrap(),
+        "10\n"
+    );
+
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn test_import_fps_xml() {
+    let dir = temp_dir("import-fps");
+    let path = dir.join("problem.xml");
+    fs::write(
+        &path,
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<fps version="1.2">
+  <item>
+    <title><![CDATA[A+B Problem]]></title>
+    <time_limit unit="s"><![CDATA[2]]></time_limit>
+    <memory_limit unit="mb"><![CDATA[128]]></memory_limit>
+    <test_input><![CDATA[1 2
+]]></test_input>
+    <test_output><![CDATA[3
+]]></test_output>
+    <test_input><![CDATA[3 4
+]]></test_input>
+    <test_output><![CDATA[7
+]]></test_output>
+    <spj language="C++"><![CDATA[int main() { return 0; }]]></spj>
+  </item>
+</fps>"#,
+    )
+    .unwrap();
+
+    let package = import_package(PackageFormat::Fps, &path).unwrap();
+    assert_eq!(package.config.name, "A+B Problem");
+    assert_eq!(package.config.time_limit_ms, Some(2000));
+    assert_eq!(package.config.memory_limit_mb, Some(128));
+    assert_eq!(package.test_cases.len(), 2);
+    assert!(matches!(
+        &package.test_cases[1].expected_output,
+        TestData::Inline(data) if data == b"7\n"
+    ));
+    assert!(matches!(
+        package.config.checker,
+        CheckerSpec::Custom { ref language, .. } if language == "cpp17"
+    ));
+
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn test_import_polygon_package() {
+    let dir = temp_dir("import-polygon");
+    fs::create_dir_all(dir.join("tests")).unwrap();
+    fs::write(
+        dir.join("problem.xml"),
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<problem short-name="aplusb">
+  <names>
+    <name language="english" value="A plus B"/>
+  </names>
+  <judging>
+    <testset name="tests">
+      <time-limit>1500</time-limit>
+      <memory-limit>268435456</memory-limit>
+      <test-count>3</test-count>
+      <input-path-pattern>tests/%02d</input-path-pattern>
+      <answer-path-pattern>tests/%02d.a</answer-path-pattern>
+      <tests>
+        <test method="manual" group="1" points="10"/>
+        <test method="manual" group="2" points="20"/>
+        <test method="manual" group="2" points="20"/>
+      </tests>
+      <groups>
+        <group name="1" points="30"/>
+      </groups>
+    </testset>
+  </judging>
+  <assets>
+    <checker name="std::rcmp6.cpp" type="testlib">
+      <source path="files/check.cpp" type="cpp.g++17"/>
+    </checker>
+    <validators>
+      <validator>
+        <source path="files/val.py" type="python.3"/>
+      </validator>
+    </validators>
+  </assets>
+</problem>"#,
+    )
+    .unwrap();
+    fs::create_dir_all(dir.join("files")).unwrap();
+    fs::write(dir.join("files/val.py"), "input()\n").unwrap();
+    for i in 1..=3 {
+        fs::write(dir.join(format!("tests/{:02}", i)), format!("{}\n", i)).unwrap();
+        fs::write(
+            dir.join(format!("tests/{:02}.a", i)),
+            format!("{}\n", i * 2),
+        )
+        .unwrap();
+    }
+
+    let package = import_package(PackageFormat::Polygon, &dir).unwrap();
+    assert_eq!(package.config.name, "A plus B");
+    assert_eq!(package.config.time_limit_ms, Some(1500));
+    assert_eq!(package.config.memory_limit_mb, Some(256));
+    assert_eq!(
+        package.config.checker,
+        CheckerSpec::Float { precision: 1e-6 }
+    );
+    assert_eq!(package.test_cases.len(), 3);
+    assert_eq!(
+        package
+            .config
+            .validator
+            .as_ref()
+            .map(|v| v.language.as_str()),
+        Some("python3")
+    );
+
+    let subtasks = &package.config.subtasks;
+    assert_eq!(subtasks.len(), 2);
+    assert_eq!(subtasks[0].score, 30.0);
+    assert_eq!(subtasks[1].score, 40.0);
+    assert_eq!(subtasks[1].test_cases, vec![1, 2]);
+
+    fs::remove_dir_all(dir).ok();
+}
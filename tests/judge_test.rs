@@ -1,3 +1,4 @@
+use opti_neko::judge::{CheckerSpec, CheckerStyle};
 use opti_neko::{Judge, JudgeConfig, JudgeStatus, TestCase};
 use std::time::Duration;
 
@@ -21,8 +22,8 @@ async fn test_accepted_submission() {
     };
 
     let test_case = TestCase {
-        input: "1 2\n".to_string(),
-        expected_output: "3\n".to_string(),
+        input: "1 2\n".into(),
+        expected_output: "3\n".into(),
     };
 
     let judge = Judge::new(config);
@@ -50,8 +51,8 @@ async fn test_wrong_answer() {
     };
 
     let test_case = TestCase {
-        input: "1 2\n".to_string(),
-        expected_output: "3\n".to_string(),
+        input: "1 2\n".into(),
+        expected_output: "3\n".into(),
     };
 
     let judge = Judge::new(config);
@@ -77,8 +78,8 @@ async fn test_compilation_error() {
     };
 
     let test_case = TestCase {
-        input: "".to_string(),
-        expected_output: "Hello World!\n".to_string(),
+        input: "".into(),
+        expected_output: "Hello World!\n".into(),
     };
 
     let judge = Judge::new(config);
@@ -107,16 +108,16 @@ async fn test_multiple_test_cases() {
 
     let test_cases = vec![
         TestCase {
-            input: "1 2\n".to_string(),
-            expected_output: "3\n".to_string(),
+            input: "1 2\n".into(),
+            expected_output: "3\n".into(),
         },
         TestCase {
-            input: "5 7\n".to_string(),
-            expected_output: "12\n".to_string(),
+            input: "5 7\n".into(),
+            expected_output: "12\n".into(),
         },
         TestCase {
-            input: "0 0\n".to_string(),
-            expected_output: "0\n".to_string(),
+            input: "0 0\n".into(),
+            expected_output: "0\n".into(),
         },
     ];
 
@@ -153,12 +154,12 @@ async fn test_partial_correct() {
 
     let test_cases = vec![
         TestCase {
-            input: "1 2\n".to_string(),
-            expected_output: "3\n".to_string(),
+            input: "1 2\n".into(),
+            expected_output: "3\n".into(),
         },
         TestCase {
-            input: "5 3\n".to_string(),
-            expected_output: "8\n".to_string(),
+            input: "5 3\n".into(),
+            expected_output: "8\n".into(),
         },
     ];
 
@@ -170,6 +171,99 @@ async fn test_partial_correct() {
     assert_eq!(result.test_case_results[1].status, JudgeStatus::WrongAnswer);
 }
 
+#[tokio::test]
+async fn test_output_diff() {
+    let config = JudgeConfig {
+        time_limit: Duration::from_secs(1),
+        memory_limit: 256 * 1024 * 1024,
+        language: "python3".to_string(),
+        source_code: "for i in range(1, 11):\n    print(60 if i == 6 else i)\n".to_string(),
+    };
+    let expected: String = (1..=10).map(|i| format!("{}\n", i)).collect();
+    let test_case = TestCase {
+        input: "".into(),
+        expected_output: expected.into(),
+    };
+
+    // 默认不计算差异
+    let result = Judge::new(config.clone()).judge(&test_case).await.unwrap();
+    assert_eq!(result.status, JudgeStatus::WrongAnswer);
+    assert_eq!(result.test_case_results[0].output_diff, None);
+
+    let result = Judge::new(config)
+        .with_output_diff(true)
+        .judge(&test_case)
+        .await
+        .unwrap();
+    let diff = result.test_case_results[0].output_diff.clone().unwrap();
+    assert_eq!((diff.line, diff.column), (6, 2));
+    assert!(!diff.truncated);
+    assert_eq!(
+        diff.unified_diff,
+        "--- expected\n+++ actual\n@@ -3,7 +3,7 @@\n 3\n 4\n 5\n-6\n+60\n 7\n 8\n 9\n"
+    );
+}
+
+#[tokio::test]
+async fn test_output_diff_long_lines() {
+    let config = JudgeConfig {
+        time_limit: Duration::from_secs(1),
+        memory_limit: 256 * 1024 * 1024,
+        language: "python3".to_string(),
+        source_code: "print('x' * 100000 + 'c')\nprint('x' * 100000)\nprint(1)\n".to_string(),
+    };
+    let long = "x".repeat(100000);
+    let test_case = TestCase {
+        input: "".into(),
+        expected_output: format!("{long}b\n{long}  \t\n2\n").into(),
+    };
+
+    let result = Judge::new(config)
+        .with_output_diff(true)
+        .judge(&test_case)
+        .await
+        .unwrap();
+    let diff = result.test_case_results[0].output_diff.clone().unwrap();
+    // 超长的行只保留前缀，第一行在保留部分之后才不同
+    assert_eq!(diff.line, 1);
+    assert!(diff.column > 200);
+    assert!(diff.unified_diff.len() <= 4096 + 32);
+    assert!(diff.unified_diff.contains("...\n"));
+    // 第二行在保留部分之后只有行尾空白不同，同样是不一致的行
+    assert_eq!(diff.unified_diff.matches("\n-xxxx").count(), 2);
+    assert_eq!(diff.unified_diff.matches("\n+xxxx").count(), 2);
+    assert!(diff.unified_diff.contains("\n-2\n+xxxx"));
+    assert!(diff.unified_diff.ends_with("\n+1\n"));
+}
+
+#[tokio::test]
+async fn test_output_diff_trailing_whitespace() {
+    let config = JudgeConfig {
+        time_limit: Duration::from_secs(1),
+        memory_limit: 256 * 1024 * 1024,
+        language: "python3".to_string(),
+        source_code: "print('1\\n2\\n3 \\n4\\n5  \\n')\n".to_string(),
+    };
+    let test_case = TestCase {
+        input: "".into(),
+        expected_output: "1\n2\n3\n4\n5\n".into(),
+    };
+
+    // 中间行的行尾空格按 Exact 比较方式不一致，差异指向该行；整个输出末尾的空白不计
+    let result = Judge::new(config)
+        .with_output_diff(true)
+        .judge(&test_case)
+        .await
+        .unwrap();
+    assert_eq!(result.status, JudgeStatus::WrongAnswer);
+    let diff = result.test_case_results[0].output_diff.clone().unwrap();
+    assert_eq!((diff.line, diff.column), (3, 2));
+    assert_eq!(
+        diff.unified_diff,
+        "--- expected\n+++ actual\n@@ -1,5 +1,5 @@\n 1\n 2\n-3\n+3 \n 4\n 5\n"
+    );
+}
+
 #[tokio::test]
 async fn test_python_submission() {
     let config = JudgeConfig {
@@ -185,12 +279,12 @@ print(a + b)
 
     let test_cases = vec![
         TestCase {
-            input: "1 2\n".to_string(),
-            expected_output: "3\n".to_string(),
+            input: "1 2\n".into(),
+            expected_output: "3\n".into(),
         },
         TestCase {
-            input: "100 200\n".to_string(),
-            expected_output: "300\n".to_string(),
+            input: "100 200\n".into(),
+            expected_output: "300\n".into(),
         },
     ];
 
@@ -221,16 +315,422 @@ func main() {
 
     let test_cases = vec![
         TestCase {
-            input: "1 2\n".to_string(),
-            expected_output: "3\n".to_string(),
+            input: "1 2\n".into(),
+            expected_output: "3\n".into(),
+        },
+        TestCase {
+            input: "100 200\n".into(),
+            expected_output: "300\n".into(),
+        },
+    ];
+
+    let judge = Judge::new(config);
+    let result = judge.judge_all(&test_cases).await.unwrap();
+    assert_eq!(result.status, JudgeStatus::Accepted);
+}
+
+#[tokio::test]
+async fn test_rust_submission() {
+    let config = JudgeConfig {
+        time_limit: Duration::from_secs(1),
+        memory_limit: 256 * 1024 * 1024,
+        language: "rust".to_string(),
+        source_code: r#"
+use std::io::stdin;
+
+fn main() {
+    let mut line = String::new();
+    stdin().read_line(&mut line).unwrap();
+    let sum: i64 = line.split_whitespace().map(|x| x.parse::<i64>().unwrap()).sum();
+    println!("{}", sum);
+}
+        "#
+        .to_string(),
+    };
+
+    let test_cases = vec![
+        TestCase {
+            input: "1 2\n".into(),
+            expected_output: "3\n".into(),
+        },
+        TestCase {
+            input: "100 200\n".into(),
+            expected_output: "300\n".into(),
+        },
+    ];
+
+    let judge = Judge::new(config);
+    let result = judge.judge_all(&test_cases).await.unwrap();
+    assert_eq!(result.status, JudgeStatus::Accepted);
+}
+
+#[tokio::test]
+async fn test_javascript_submission() {
+    let config = JudgeConfig {
+        time_limit: Duration::from_secs(1),
+        memory_limit: 256 * 1024 * 1024,
+        language: "javascript".to_string(),
+        source_code: r#"
+const lines = require("fs").readFileSync(0, "utf8").trim().split("\n");
+const [a, b] = lines[0].split(" ").map(Number);
+console.log(a + b);
+        "#
+        .to_string(),
+    };
+
+    let test_cases = vec![
+        TestCase {
+            input: "1 2\n".into(),
+            expected_output: "3\n".into(),
+        },
+        TestCase {
+            input: "100 200\n".into(),
+            expected_output: "300\n".into(),
+        },
+    ];
+
+    let judge = Judge::new(config);
+    let result = judge.judge_all(&test_cases).await.unwrap();
+    assert_eq!(result.status, JudgeStatus::Accepted);
+}
+
+#[tokio::test]
+async fn test_cpp_language_variant() {
+    let config = JudgeConfig {
+        time_limit: Duration::from_secs(1),
+        memory_limit: 256 * 1024 * 1024,
+        language: "cpp20".to_string(),
+        source_code: r#"
+            #include <iostream>
+            #include <numbers>
+            using namespace std;
+            int main() {
+            #ifdef ONLINE_JUDGE
+                cout << (numbers::pi > 3 ? "cpp20" : "") << endl;
+            #endif
+                return 0;
+            }
+        "#
+        .to_string(),
+    };
+
+    let test_case = TestCase {
+        input: "".into(),
+        expected_output: "cpp20\n".into(),
+    };
+
+    let judge = Judge::new(config);
+    let result = judge.judge(&test_case).await.unwrap();
+    assert_eq!(result.status, JudgeStatus::Accepted);
+}
+
+#[tokio::test]
+async fn test_compilation_output_is_bounded() {
+    let config = JudgeConfig {
+        time_limit: Duration::from_secs(1),
+        memory_limit: 256 * 1024 * 1024,
+        language: "cpp".to_string(),
+        source_code: "int x = ;\n".repeat(20000),
+    };
+
+    let test_case = TestCase {
+        input: "".into(),
+        expected_output: "".into(),
+    };
+
+    let judge = Judge::new(config);
+    let result = judge.judge(&test_case).await.unwrap();
+    assert_eq!(result.status, JudgeStatus::CompilationError);
+
+    let message = result.error_message.unwrap();
+    assert!(message.len() < 128 * 1024);
+    assert!(message.ends_with("(compiler output truncated)"));
+}
+
+#[tokio::test]
+async fn test_file_backed_test_cases() {
+    let config = JudgeConfig {
+        time_limit: Duration::from_secs(1),
+        memory_limit: 256 * 1024 * 1024,
+        language: "python".to_string(),
+        source_code: r#"
+import sys
+n = int(sys.stdin.readline())
+sys.stdout.write("\n".join(str(i) for i in range(n)) + "\n\n")
+        "#
+        .to_string(),
+    };
+
+    let dir = std::env::temp_dir().join(format!("opti-neko-test-files-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let input = dir.join("1.in");
+    let output = dir.join("1.out");
+    let wrong_output = dir.join("2.out");
+    std::fs::write(&input, "100000\n").unwrap();
+    let expected: Vec<String> = (0..100000).map(|i| i.to_string()).collect();
+    std::fs::write(&output, expected.join("\n")).unwrap();
+    std::fs::write(&wrong_output, expected[..99999].join("\n")).unwrap();
+
+    let test_cases = vec![
+        TestCase {
+            input: input.clone().into(),
+            expected_output: output.into(),
+        },
+        TestCase {
+            input: input.into(),
+            expected_output: wrong_output.into(),
+        },
+    ];
+
+    let judge = Judge::new(config);
+    let result = judge.judge_all(&test_cases).await.unwrap();
+    std::fs::remove_dir_all(dir).ok();
+
+    assert_eq!(result.test_case_results[0].status, JudgeStatus::Accepted);
+    assert_eq!(result.test_case_results[1].status, JudgeStatus::WrongAnswer);
+}
+
+#[tokio::test]
+async fn test_binary_output() {
+    let config = JudgeConfig {
+        time_limit: Duration::from_secs(1),
+        memory_limit: 256 * 1024 * 1024,
+        language: "python".to_string(),
+        source_code: r#"
+import sys
+sys.stdout.buffer.write(b"\xff\xfe\n")
+        "#
+        .to_string(),
+    };
+
+    let test_cases = vec![
+        TestCase {
+            input: "".into(),
+            expected_output: vec![0xff, 0xfe].into(),
         },
         TestCase {
-            input: "100 200\n".to_string(),
-            expected_output: "300\n".to_string(),
+            input: "".into(),
+            expected_output: "3\n".into(),
         },
     ];
 
     let judge = Judge::new(config);
     let result = judge.judge_all(&test_cases).await.unwrap();
+
+    assert_eq!(result.test_case_results[0].status, JudgeStatus::Accepted);
+    assert_eq!(result.test_case_results[0].actual_output, b"\xff\xfe\n");
+    assert_eq!(result.test_case_results[1].status, JudgeStatus::WrongAnswer);
+}
+
+#[tokio::test]
+async fn test_token_checker() {
+    let config = JudgeConfig {
+        time_limit: Duration::from_secs(1),
+        memory_limit: 256 * 1024 * 1024,
+        language: "python3".to_string(),
+        source_code: "print('1   2')\nprint()\nprint('3')\n".to_string(),
+    };
+
+    let test_case = TestCase {
+        input: "".into(),
+        expected_output: "1 2 3\n".into(),
+    };
+
+    let result = Judge::new(config.clone()).judge(&test_case).await.unwrap();
+    assert_eq!(result.status, JudgeStatus::WrongAnswer);
+
+    let result = Judge::new(config)
+        .with_checker(CheckerSpec::Tokens)
+        .judge(&test_case)
+        .await
+        .unwrap();
     assert_eq!(result.status, JudgeStatus::Accepted);
 }
+
+#[tokio::test]
+async fn test_custom_checker() {
+    let config = JudgeConfig {
+        time_limit: Duration::from_secs(1),
+        memory_limit: 256 * 1024 * 1024,
+        language: "python3".to_string(),
+        source_code: "n = int(input())\nprint(n, 0)\n".to_string(),
+    };
+
+    // HUSTOJ 风格：参数依次为 输入、答案、选手输出，接受任意一组和为 n 的两个数
+    let checker = CheckerSpec::Custom {
+        language: "cpp17".to_string(),
+        source_code: r#"
+            #include <fstream>
+            int main(int argc, char *argv[]) {
+                std::ifstream input(argv[1]), output(argv[3]);
+                long long n, a, b;
+                input >> n;
+                if (!(output >> a >> b)) return 1;
+                return a + b == n ? 0 : 1;
+            }
+        "#
+        .to_string(),
+        style: CheckerStyle::Hustoj,
+    };
+
+    let test_cases = vec![TestCase {
+        input: "5\n".into(),
+        expected_output: "2 3\n".into(),
+    }];
+
+    let result = Judge::new(config)
+        .with_checker(checker)
+        .judge_all(&test_cases)
+        .await
+        .unwrap();
+    assert_eq!(result.status, JudgeStatus::Accepted);
+}
+
+#[tokio::test]
+async fn test_run_with_custom_input() {
+    let config = JudgeConfig {
+        time_limit: Duration::from_secs(1),
+        memory_limit: 256 * 1024 * 1024,
+        language: "python3".to_string(),
+        source_code: r#"
+import sys
+n = int(input())
+print(n * 2)
+print("debug", n, file=sys.stderr)
+sys.exit(3)
+"#
+        .to_string(),
+    };
+
+    let result = Judge::new(config).run(&"21\n".into()).await.unwrap();
+    assert_eq!(result.status, JudgeStatus::RuntimeError);
+    assert_eq!(result.exit_code, Some(3));
+    assert_eq!(result.stdout, b"42\n");
+    assert_eq!(result.stderr, b"debug 21\n");
+}
+
+#[tokio::test]
+async fn test_run_time_limit_exceeded() {
+    let config = JudgeConfig {
+        time_limit: Duration::from_millis(200),
+        memory_limit: 256 * 1024 * 1024,
+        language: "python3".to_string(),
+        source_code: "while True:\n    pass\n".to_string(),
+    };
+
+    let start = std::time::Instant::now();
+    let result = Judge::new(config).run(&"".into()).await.unwrap();
+    assert_eq!(result.status, JudgeStatus::TimeLimitExceeded);
+    assert_eq!(result.exit_code, None);
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[tokio::test]
+async fn test_time_limit_exceeded() {
+    let config = JudgeConfig {
+        time_limit: Duration::from_millis(200),
+        memory_limit: 256 * 1024 * 1024,
+        language: "python3".to_string(),
+        source_code: "while True:\n    pass\n".to_string(),
+    };
+
+    let test_case = TestCase {
+        input: "".into(),
+        expected_output: "".into(),
+    };
+
+    let result = Judge::new(config).judge(&test_case).await.unwrap();
+    assert_eq!(result.status, JudgeStatus::TimeLimitExceeded);
+}
+
+#[tokio::test]
+async fn test_output_file_limit() {
+    let config = JudgeConfig {
+        time_limit: Duration::from_secs(10),
+        memory_limit: 256 * 1024 * 1024,
+        language: "c".to_string(),
+        source_code: r#"
+#include <stdio.h>
+#include <string.h>
+int main() {
+    static char buf[1 << 16];
+    memset(buf, 'a', sizeof buf);
+    for (;;) fwrite(buf, 1, sizeof buf, stdout);
+}
+        "#
+        .to_string(),
+    };
+
+    let test_case = TestCase {
+        input: "".into(),
+        expected_output: "".into(),
+    };
+
+    let result = Judge::new(config).judge(&test_case).await.unwrap();
+    assert_eq!(result.status, JudgeStatus::RuntimeError);
+}
+
+#[tokio::test]
+async fn test_exact_checker_whitespace() {
+    let config = JudgeConfig {
+        time_limit: Duration::from_secs(1),
+        memory_limit: 256 * 1024 * 1024,
+        language: "python3".to_string(),
+        source_code: "print(input())\n".to_string(),
+    };
+
+    let test_cases = vec![
+        TestCase {
+            input: "  1 2 \t\n".into(),
+            expected_output: "\n1 2\n\n\n".into(),
+        },
+        TestCase {
+            input: "1  2\n".into(),
+            expected_output: "1 2\n".into(),
+        },
+        TestCase {
+            input: "1 2 3\n".into(),
+            expected_output: "1 2\n".into(),
+        },
+    ];
+
+    let result = Judge::new(config).judge_all(&test_cases).await.unwrap();
+    assert_eq!(result.test_case_results[0].status, JudgeStatus::Accepted);
+    assert_eq!(result.test_case_results[1].status, JudgeStatus::WrongAnswer);
+    assert_eq!(result.test_case_results[2].status, JudgeStatus::WrongAnswer);
+}
+
+#[tokio::test]
+async fn test_cancelled_run_kills_process() {
+    let pid_file = std::env::temp_dir().join(format!("opti-neko-test-pid-{}", std::process::id()));
+    std::fs::remove_file(&pid_file).ok();
+    let config = JudgeConfig {
+        time_limit: Duration::from_secs(30),
+        memory_limit: 256 * 1024 * 1024,
+        language: "python3".to_string(),
+        source_code: format!(
+            "import os, time\nopen({:?}, 'w').write(str(os.getpid()))\ntime.sleep(60)\n",
+            pid_file.to_str().unwrap()
+        ),
+    };
+    let test_case = TestCase {
+        input: "".into(),
+        expected_output: "".into(),
+    };
+
+    // 评测任务被中止（如退出时等待超时）时，程序不应继续运行
+    let judge = Judge::new(config);
+    let run = tokio::time::timeout(Duration::from_secs(3), judge.judge(&test_case)).await;
+    assert!(run.is_err());
+
+    let pid = std::fs::read_to_string(&pid_file).unwrap();
+    std::fs::remove_file(&pid_file).ok();
+    let proc = std::path::Path::new("/proc").join(pid.trim());
+    for _ in 0..50 {
+        if !proc.exists() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("process {} is still running", pid.trim());
+}
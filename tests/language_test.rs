@@ -0,0 +1,16 @@
+use opti_neko::judge::language::Language;
+
+#[test]
+fn test_optimization_flags_from_settings() {
+    // 读取配置前覆盖 C++ 的优化级别，C 仍使用配置文件中的默认值
+    std::env::set_var("NEKO_CPP__OPT_LEVEL", "3");
+    let command = |name: &str| Language::resolve(name).unwrap().compile_command().unwrap();
+
+    let cpp = command("cpp17");
+    assert!(cpp.contains(&"-O3".to_string()), "{:?}", cpp);
+    assert!(cpp.contains(&"-std=c++17".to_string()));
+
+    let c = command("c11");
+    assert!(c.contains(&"-O2".to_string()), "{:?}", c);
+    assert!(c.contains(&"-std=c11".to_string()));
+}
@@ -0,0 +1,139 @@
+use opti_neko::config::LimitsSettings;
+use opti_neko::limits::{check_problem_data, check_test_case_id, max_message_bytes, RequestSize};
+
+fn request() -> RequestSize {
+    RequestSize {
+        time_limit_ms: 1000,
+        memory_limit_mb: 256,
+        source_bytes: 100,
+        test_cases: 10,
+        input_bytes: 1024,
+    }
+}
+
+fn limits() -> LimitsSettings {
+    LimitsSettings {
+        max_time_limit_ms: 5000,
+        max_memory_limit_mb: 512,
+        max_source_kb: 64,
+        max_test_cases: 100,
+        max_input_mb: 1,
+        max_problem_mb: 2,
+    }
+}
+
+#[test]
+fn test_accepts_request_within_limits() {
+    assert_eq!(request().check(&limits()), Ok(()));
+}
+
+#[test]
+fn test_rejects_non_positive_limits() {
+    let negative_memory = RequestSize {
+        memory_limit_mb: -1,
+        ..request()
+    };
+    assert_eq!(
+        negative_memory.check(&limits()),
+        Err("内存限制必须大于0，实际为 -1".to_string())
+    );
+
+    let zero_time = RequestSize {
+        time_limit_ms: 0,
+        ..request()
+    };
+    assert!(zero_time.check(&limits()).unwrap_err().contains("时间限制"));
+}
+
+#[test]
+fn test_rejects_each_exceeded_limit() {
+    let cases = [
+        (
+            RequestSize {
+                time_limit_ms: i32::MAX.into(),
+                ..request()
+            },
+            "时间限制 2147483647 ms 超过上限 5000 ms",
+        ),
+        (
+            RequestSize {
+                memory_limit_mb: 1024,
+                ..request()
+            },
+            "内存限制 1024 MB 超过上限 512 MB",
+        ),
+        (
+            RequestSize {
+                source_bytes: 64 * 1024 + 1,
+                ..request()
+            },
+            "源代码大小 65537 bytes 超过上限 65536 bytes",
+        ),
+        (
+            RequestSize {
+                test_cases: 10_000,
+                ..request()
+            },
+            "测试点数 10000 个 超过上限 100 个",
+        ),
+        (
+            RequestSize {
+                input_bytes: 2 * 1024 * 1024,
+                ..request()
+            },
+            "输入数据总大小 2097152 bytes 超过上限 1048576 bytes",
+        ),
+    ];
+    for (size, message) in cases {
+        assert_eq!(size.check(&limits()), Err(message.to_string()));
+    }
+}
+
+#[test]
+fn test_zero_means_unlimited() {
+    let unlimited = LimitsSettings {
+        max_time_limit_ms: 0,
+        max_memory_limit_mb: 0,
+        max_source_kb: 0,
+        max_test_cases: 0,
+        max_input_mb: 0,
+        max_problem_mb: 0,
+    };
+    let huge = RequestSize {
+        time_limit_ms: i32::MAX.into(),
+        memory_limit_mb: i32::MAX.into(),
+        source_bytes: usize::MAX,
+        test_cases: 1_000_000,
+        input_bytes: usize::MAX,
+    };
+    assert_eq!(huge.check(&unlimited), Ok(()));
+}
+
+#[test]
+fn test_problem_data_limits() {
+    assert_eq!(check_problem_data(100, 2 * 1024 * 1024, &limits()), Ok(()));
+    assert!(check_problem_data(101, 0, &limits()).is_err());
+    assert!(check_problem_data(1, 2 * 1024 * 1024 + 1, &limits()).is_err());
+}
+
+#[test]
+fn test_chunk_test_case_id_in_range() {
+    assert_eq!(check_test_case_id(0, 3), Ok(0));
+    assert_eq!(check_test_case_id(2, 3), Ok(2));
+    // 超出声明的测试点数或为负数的编号在收到分块时拒绝
+    assert!(check_test_case_id(3, 3).is_err());
+    assert!(check_test_case_id(i32::MAX, 3).is_err());
+    assert!(check_test_case_id(-1, 3).is_err());
+}
+
+#[test]
+fn test_message_size_covers_input_limit() {
+    let bytes = max_message_bytes(&limits());
+    assert!(bytes > 1024 * 1024 + 64 * 1024);
+
+    let unlimited = LimitsSettings {
+        max_input_mb: 0,
+        ..limits()
+    };
+    assert_eq!(max_message_bytes(&unlimited), usize::MAX);
+}
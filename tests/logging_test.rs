@@ -0,0 +1,96 @@
+use opti_neko::logging;
+use opti_neko::{Judge, JudgeConfig, TestCase};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{info_span, Instrument, Level};
+
+/// 收集日志输出，用于检查 JSON 日志内容
+#[derive(Clone, Default)]
+struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Buffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_redacts_by_default() {
+    let source = "print('secret')\n";
+    assert_eq!(
+        logging::source(source).to_string(),
+        format!("<redacted {} bytes>", source.len())
+    );
+    assert_eq!(
+        logging::test_data(&"42\n".into()).to_string(),
+        "<redacted 3 bytes>"
+    );
+    assert_eq!(logging::output(b"42\n").to_string(), "<redacted 3 bytes>");
+}
+
+#[test]
+fn test_submission_ids_are_unique() {
+    assert_ne!(logging::submission_id(), logging::submission_id());
+}
+
+#[tokio::test]
+async fn test_judge_logs_carry_spans() {
+    let buffer = Buffer::default();
+    let writer = buffer.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .json()
+        .with_span_list(true)
+        .with_max_level(Level::DEBUG)
+        .with_writer(move || writer.clone())
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let judge = Judge::new(JudgeConfig {
+        time_limit: Duration::from_secs(1),
+        memory_limit: 256 * 1024 * 1024,
+        language: "python".to_string(),
+        source_code: "print(input())\n".to_string(),
+    });
+    let test_cases = vec![
+        TestCase {
+            input: "hidden-input\n".into(),
+            expected_output: "hidden-input\n".into(),
+        },
+        TestCase {
+            input: "2\n".into(),
+            expected_output: "2\n".into(),
+        },
+    ];
+    let span = info_span!("submission", submission_id = "sub-1");
+    judge.judge_all(&test_cases).instrument(span).await.unwrap();
+
+    let logs = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    let lines: Vec<serde_json::Value> = logs
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+    // 每个测试点的日志都带有提交 ID、语言和测试点序号
+    let finished: Vec<&serde_json::Value> = lines
+        .iter()
+        .filter(|line| line["fields"]["message"] == "测试点评测完成")
+        .collect();
+    assert_eq!(finished.len(), 2);
+    for (i, line) in finished.iter().enumerate() {
+        let spans = line["spans"].as_array().unwrap();
+        assert_eq!(spans[0]["submission_id"], "sub-1");
+        assert_eq!(spans[1]["name"], "judge");
+        assert_eq!(spans[1]["language"], "python3");
+        assert_eq!(spans[2]["name"], "test_case");
+        assert_eq!(spans[2]["index"], i + 1);
+    }
+
+    // 测试数据默认脱敏
+    assert!(!logs.contains("hidden-input"));
+    assert!(logs.contains("<redacted 13 bytes>"));
+}
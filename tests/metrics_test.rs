@@ -0,0 +1,172 @@
+use std::fs;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+#[allow(clippy::all, dead_code)]
+mod judge_grpc_service {
+    tonic::include_proto!("judge_grpc_service");
+}
+
+use judge_grpc_service::judge_grpc_service_client::JudgeGrpcServiceClient;
+use judge_grpc_service::{SubmitRequest, TestCase};
+
+/// 测试结束时结束服务进程
+struct Server(Child);
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.0.kill().ok();
+        self.0.wait().ok();
+    }
+}
+
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+/// 以 HTTP/1.1 请求 `/metrics`，返回响应正文
+fn scrape(port: u16) -> String {
+    // 指标服务与 gRPC 服务分别启动，稍等其开始监听
+    let mut stream = (0..50)
+        .find_map(|_| {
+            TcpStream::connect(("127.0.0.1", port))
+                .inspect_err(|_| std::thread::sleep(Duration::from_millis(100)))
+                .ok()
+        })
+        .expect("metrics server did not start");
+    write!(
+        stream,
+        "GET /metrics HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n"
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+    response
+        .split_once("\r\n\r\n")
+        .map(|(_, body)| body.to_string())
+        .unwrap_or_default()
+}
+
+/// 指标文本中某个样本的值，`sample` 为指标名和标签
+fn value(text: &str, sample: &str) -> Option<f64> {
+    text.lines()
+        .find_map(|line| line.strip_prefix(sample)?.strip_prefix(' '))
+        .and_then(|v| v.split_whitespace().next()?.parse().ok())
+}
+
+fn submit_request(test_cases: &[(&str, &str)]) -> SubmitRequest {
+    SubmitRequest {
+        language: "python3".to_string(),
+        source_code: "print(input())\n".to_string(),
+        time_limit: 1000,
+        memory_limit: 256,
+        test_cases: test_cases
+            .iter()
+            .map(|(input, output)| TestCase {
+                input: input.as_bytes().to_vec(),
+                expected_output: output.as_bytes().to_vec(),
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_submissions_are_exported() {
+    let dir = std::env::temp_dir().join(format!("opti-neko-test-metrics-{}", std::process::id()));
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).unwrap();
+
+    let (grpc_port, metrics_port) = (free_port(), free_port());
+    let _server = Server(
+        Command::new(env!("CARGO_BIN_EXE_opti-neko"))
+            .arg("serve")
+            .env("NEKO_SERVER__LISTEN", format!("127.0.0.1:{}", grpc_port))
+            .env("NEKO_METRICS__ENABLED", "true")
+            .env(
+                "NEKO_METRICS__LISTEN",
+                format!("127.0.0.1:{}", metrics_port),
+            )
+            .env("NEKO_QUEUE__WORKERS", "2")
+            .env("NEKO_STORE__PATH", dir.join("submissions.db"))
+            .env("NEKO_PROBLEM__DATA_DIR", dir.join("testcases"))
+            .env("NEKO_LANGUAGES__ENABLED", "python3")
+            .env("NEKO_HEALTH__LANGUAGES", "python3")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap(),
+    );
+
+    // 等待服务启动
+    let mut client = None;
+    for _ in 0..100 {
+        match JudgeGrpcServiceClient::connect(format!("http://127.0.0.1:{}", grpc_port)).await {
+            Ok(c) => {
+                client = Some(c);
+                break;
+            }
+            Err(_) => tokio::time::sleep(Duration::from_millis(200)).await,
+        }
+    }
+    let mut client = client.expect("server did not start");
+    // 启动时的语言自检也会编译运行，只比较提交前后的差值
+    let before = scrape(metrics_port);
+
+    let accepted = client
+        .submit(submit_request(&[("1\n", "1\n")]))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(accepted.status, 0);
+    let wrong = client
+        .submit(submit_request(&[("1\n", "1\n"), ("2\n", "3\n")]))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(wrong.status, 1);
+
+    let text = scrape(metrics_port);
+    let submissions = |verdict: &str| {
+        value(
+            &text,
+            &format!(
+                r#"opti_neko_submissions_total{{language="python3",verdict="{}"}}"#,
+                verdict
+            ),
+        )
+    };
+    assert_eq!(submissions("Accepted"), Some(1.0));
+    assert_eq!(submissions("WrongAnswer"), Some(1.0));
+
+    // 每次提交编译一次，每个测试点运行一次
+    let delta =
+        |sample: &str| value(&text, sample).unwrap() - value(&before, sample).unwrap_or(0.0);
+    assert_eq!(
+        delta(r#"opti_neko_compile_duration_seconds_count{language="python3"}"#),
+        2.0
+    );
+    assert_eq!(
+        delta(r#"opti_neko_run_duration_seconds_count{language="python3"}"#),
+        3.0
+    );
+    assert!(delta(r#"opti_neko_run_duration_seconds_sum{language="python3"}"#) > 0.0);
+    assert_eq!(
+        delta(r#"opti_neko_run_duration_seconds_bucket{le="+Inf",language="python3"}"#),
+        3.0
+    );
+
+    // 评测结束后队列为空
+    assert_eq!(value(&text, "opti_neko_workers"), Some(2.0));
+    assert_eq!(value(&text, "opti_neko_busy_workers"), Some(0.0));
+    assert_eq!(value(&text, "opti_neko_queue_depth"), Some(0.0));
+
+    fs::remove_dir_all(dir).ok();
+}
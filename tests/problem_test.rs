@@ -0,0 +1,108 @@
+use opti_neko::problem::{ProblemStore, VersionConflict};
+use std::fs;
+use std::path::PathBuf;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("opti-neko-test-{}-{}", name, std::process::id()));
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_upload_and_load_problem() {
+    let root = temp_dir("problem-upload");
+    let store = ProblemStore::new(&root);
+    assert_eq!(store.current_version("1001").unwrap(), None);
+
+    let mut upload = store.begin_upload("1001", "v1").unwrap();
+    upload.write_chunk(0, false, b"1 ").unwrap();
+    upload.write_chunk(0, false, b"2\n").unwrap();
+    upload.write_chunk(0, true, b"3\n").unwrap();
+    upload.write_chunk(1, false, b"5 7\n").unwrap();
+    upload.write_chunk(1, true, b"12\n").unwrap();
+    upload.finish(2, &[]).unwrap();
+
+    assert_eq!(
+        store.current_version("1001").unwrap().as_deref(),
+        Some("v1")
+    );
+
+    let manifest = store.load("1001", None).unwrap();
+    let test_cases = store.test_cases(&manifest);
+    assert_eq!(test_cases.len(), 2);
+    assert_eq!(
+        fs::read_to_string(root.join("1001/v1/1.in")).unwrap(),
+        "1 2\n"
+    );
+    assert_eq!(
+        fs::read_to_string(root.join("1001/v1/2.out")).unwrap(),
+        "12\n"
+    );
+
+    fs::remove_dir_all(root).ok();
+}
+
+#[test]
+fn test_problem_integrity_checks() {
+    let root = temp_dir("problem-integrity");
+    let store = ProblemStore::new(&root);
+
+    let mut upload = store.begin_upload("1002", "v1").unwrap();
+    upload.write_chunk(0, false, b"1 2\n").unwrap();
+    let wrong_digest = vec![("0".repeat(64), "0".repeat(64))];
+    assert!(upload.finish(1, &wrong_digest).is_err());
+    assert_eq!(store.current_version("1002").unwrap(), None);
+
+    let mut upload = store.begin_upload("1002", "v2").unwrap();
+    upload.write_chunk(0, false, b"1 2\n").unwrap();
+    upload.write_chunk(0, true, b"3\n").unwrap();
+    upload.finish(1, &[]).unwrap();
+
+    fs::write(root.join("1002").join("v2").join("1.out"), b"4\n").unwrap();
+    assert!(store.load("1002", Some("v2")).is_err());
+    assert!(store.begin_upload("../etc", "v1").is_err());
+
+    fs::remove_dir_all(root).ok();
+}
+
+#[test]
+fn test_existing_version_must_match() {
+    let root = temp_dir("problem-existing-version");
+    let store = ProblemStore::new(&root);
+
+    let upload_v1 = |output: &[u8]| {
+        let mut upload = store.begin_upload("1003", "v1").unwrap();
+        upload.write_chunk(0, false, b"1 2\n").unwrap();
+        upload.write_chunk(0, true, output).unwrap();
+        upload.finish(1, &[])
+    };
+    let manifest = upload_v1(b"3\n").unwrap();
+
+    let mut upload = store.begin_upload("1003", "v2").unwrap();
+    upload.write_chunk(0, false, b"2 2\n").unwrap();
+    upload.write_chunk(0, true, b"4\n").unwrap();
+    upload.finish(1, &[]).unwrap();
+
+    // 内容不同时拒绝，已有数据和当前版本都不变
+    let error = upload_v1(b"4\n").unwrap_err();
+    assert!(error.downcast_ref::<VersionConflict>().is_some());
+    assert_eq!(
+        fs::read_to_string(root.join("1003/v1/1.out")).unwrap(),
+        "3\n"
+    );
+    assert_eq!(
+        store.current_version("1003").unwrap().as_deref(),
+        Some("v2")
+    );
+
+    // 内容相同时重新指向已有版本
+    assert_eq!(upload_v1(b"3\n").unwrap(), manifest);
+    assert_eq!(
+        store.current_version("1003").unwrap().as_deref(),
+        Some("v1")
+    );
+    store.load("1003", Some("v1")).unwrap();
+
+    fs::remove_dir_all(root).ok();
+}
@@ -0,0 +1,141 @@
+use opti_neko::queue::{JudgeQueue, Priority, TaskInfo};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+fn task(priority: Priority, user_id: &str, contest_id: &str) -> TaskInfo {
+    TaskInfo {
+        priority,
+        user_id: user_id.to_string(),
+        contest_id: contest_id.to_string(),
+    }
+}
+
+/// 占住唯一的空位，按顺序提交任务，然后放开并记录实际执行顺序
+async fn execution_order(tasks: Vec<(&'static str, TaskInfo)>) -> Vec<&'static str> {
+    let queue = JudgeQueue::new(1);
+    let order = Arc::new(Mutex::new(Vec::new()));
+    let permit = queue.acquire(TaskInfo::default()).await;
+
+    let mut handles = Vec::new();
+    for (name, task) in tasks {
+        let queue = queue.clone();
+        let order = order.clone();
+        handles.push(tokio::spawn(async move {
+            let _permit = queue.acquire(task).await;
+            order.lock().unwrap().push(name);
+        }));
+        // 保证到达顺序
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    drop(permit);
+    for handle in handles {
+        handle.await.unwrap();
+    }
+    let order = order.lock().unwrap().clone();
+    order
+}
+
+#[tokio::test]
+async fn test_priority_classes() {
+    let order = execution_order(vec![
+        ("rejudge", task(Priority::Rejudge, "a", "")),
+        ("answers", task(Priority::Answers, "e", "")),
+        ("run", task(Priority::Run, "b", "")),
+        ("practice", task(Priority::Practice, "c", "")),
+        ("contest", task(Priority::Contest, "d", "1")),
+    ])
+    .await;
+    assert_eq!(
+        order,
+        vec!["contest", "practice", "run", "answers", "rejudge"]
+    );
+}
+
+#[tokio::test]
+async fn test_fair_share_between_users() {
+    let order = execution_order(vec![
+        ("a1", task(Priority::Practice, "a", "")),
+        ("a2", task(Priority::Practice, "a", "")),
+        ("a3", task(Priority::Practice, "a", "")),
+        ("b1", task(Priority::Practice, "b", "")),
+        ("c1", task(Priority::Practice, "c", "")),
+        ("b2", task(Priority::Practice, "b", "")),
+    ])
+    .await;
+    assert_eq!(order, vec!["a1", "b1", "c1", "a2", "b2", "a3"]);
+}
+
+#[tokio::test]
+async fn test_fair_share_between_contests() {
+    let order = execution_order(vec![
+        ("x1", task(Priority::Contest, "a", "x")),
+        ("x2", task(Priority::Contest, "b", "x")),
+        ("x3", task(Priority::Contest, "c", "x")),
+        ("y1", task(Priority::Contest, "d", "y")),
+    ])
+    .await;
+    assert_eq!(order, vec!["x1", "y1", "x2", "x3"]);
+}
+
+#[tokio::test]
+async fn test_queue_status() {
+    let queue = JudgeQueue::new(1);
+    assert_eq!(queue.workers(), 1);
+    assert_eq!(JudgeQueue::new(0).workers(), 1);
+    let permit = queue.acquire(task(Priority::Practice, "a", "")).await;
+
+    let mut handles = Vec::new();
+    for task in [
+        task(Priority::Practice, "a", ""),
+        task(Priority::Practice, "a", ""),
+        task(Priority::Practice, "b", ""),
+    ] {
+        let queue = queue.clone();
+        handles.push(tokio::spawn(async move {
+            let _permit = queue.acquire(task).await;
+        }));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    let status = queue.status();
+    assert_eq!(status.workers, 1);
+    assert_eq!(status.running.len(), 1);
+    assert_eq!(status.running[0].task.user_id, "a");
+    let waiting: Vec<&str> = status
+        .waiting
+        .iter()
+        .map(|queued| queued.task.user_id.as_str())
+        .collect();
+    assert_eq!(waiting, vec!["b", "a", "a"]);
+
+    drop(permit);
+    for handle in handles {
+        handle.await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_cancelled_waiter_releases_slot() {
+    let queue = JudgeQueue::new(1);
+    let permit = queue.acquire(TaskInfo::default()).await;
+
+    let cancelled = {
+        let queue = queue.clone();
+        tokio::spawn(async move {
+            let _permit = queue.acquire(TaskInfo::default()).await;
+        })
+    };
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    cancelled.abort();
+    cancelled.await.ok();
+    assert_eq!(queue.waiting(), 0);
+
+    drop(permit);
+    let acquired = tokio::time::timeout(
+        Duration::from_secs(1),
+        queue.acquire(task(Priority::Run, "a", "")),
+    )
+    .await;
+    assert!(acquired.is_ok());
+}
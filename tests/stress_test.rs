@@ -0,0 +1,61 @@
+use opti_neko::judge::{JudgeConfig, JudgeStatus, StressTest};
+use std::time::Duration;
+
+fn python(source_code: &str) -> JudgeConfig {
+    JudgeConfig {
+        time_limit: Duration::from_secs(2),
+        memory_limit: 256 * 1024 * 1024,
+        language: "python3".to_string(),
+        source_code: source_code.to_string(),
+    }
+}
+
+const GENERATOR: &str = r#"
+import random, sys
+random.seed(int(sys.argv[1]))
+print(random.randint(1, 10), random.randint(1, 10))
+"#;
+
+const REFERENCE: &str = "a, b = map(int, input().split())\nprint(a + b)\n";
+
+#[tokio::test]
+async fn test_stress_finds_counterexample() {
+    let candidate = "a, b = map(int, input().split())\nprint(a + b + (a == 7))\n";
+    let stress = StressTest::new(
+        python(GENERATOR),
+        python(REFERENCE),
+        vec![python(REFERENCE), python(candidate)],
+    );
+
+    let report = stress.run(1..200).await.unwrap();
+    let counterexample = report.counterexample.expect("counterexample not found");
+    assert_eq!(counterexample.candidate, 1);
+    assert_eq!(counterexample.status, JudgeStatus::WrongAnswer);
+
+    let input = String::from_utf8(counterexample.input).unwrap();
+    let (a, b) = input.trim().split_once(' ').unwrap();
+    let (a, b): (i32, i32) = (a.parse().unwrap(), b.parse().unwrap());
+    assert_eq!(a, 7);
+    assert_eq!(
+        counterexample.expected_output,
+        format!("{}\n", a + b).into_bytes()
+    );
+    assert_eq!(
+        counterexample.actual_output,
+        format!("{}\n", a + b + 1).into_bytes()
+    );
+}
+
+#[tokio::test]
+async fn test_stress_all_consistent() {
+    let candidate = "print(sum(map(int, input().split())))\n";
+    let stress = StressTest::new(
+        python(GENERATOR),
+        python(REFERENCE),
+        vec![python(candidate)],
+    );
+
+    let report = stress.run(1..11).await.unwrap();
+    assert_eq!(report.tests_run, 10);
+    assert!(report.counterexample.is_none());
+}
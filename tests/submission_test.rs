@@ -0,0 +1,248 @@
+use opti_neko::judge::{JudgeConfig, JudgeResult, JudgeStatus, Subtask, TestCase, TestCaseResult};
+use opti_neko::submission::{
+    RejudgeEntry, RejudgeReport, ResultDiff, Submission, SubmissionStore, TestCaseChange,
+    VerdictTransition,
+};
+use std::path::PathBuf;
+use std::time::Duration;
+
+fn temp_db(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("opti-neko-{}-{}", name, std::process::id()));
+    std::fs::remove_dir_all(&dir).ok();
+    dir.join("submissions.db")
+}
+
+fn submission(id: &str, problem_id: &str) -> Submission {
+    Submission {
+        id: id.to_string(),
+        config: JudgeConfig {
+            time_limit: Duration::from_millis(1000),
+            memory_limit: 256 * 1024 * 1024,
+            language: "python3".to_string(),
+            source_code: "print(input())\n".to_string(),
+        },
+        problem_id: problem_id.to_string(),
+        test_cases: if problem_id.is_empty() {
+            vec![TestCase {
+                input: "1\n".into(),
+                expected_output: "1\n".into(),
+            }]
+        } else {
+            Vec::new()
+        },
+        user_id: "alice".to_string(),
+        contest_id: String::new(),
+        created_at: 1_700_000_000,
+    }
+}
+
+fn result(statuses: &[JudgeStatus]) -> JudgeResult {
+    let test_case_results: Vec<TestCaseResult> = statuses
+        .iter()
+        .enumerate()
+        .map(|(id, status)| TestCaseResult {
+            status: status.clone(),
+            time_used: Duration::from_micros(1500),
+            memory_used: 4096,
+            actual_output: format!("out{}\n", id).into_bytes(),
+            test_case_id: id,
+            output_diff: None,
+        })
+        .collect();
+    JudgeResult {
+        status: statuses
+            .iter()
+            .find(|s| **s != JudgeStatus::Accepted)
+            .cloned()
+            .unwrap_or(JudgeStatus::Accepted),
+        time_used: Duration::from_micros(1500),
+        memory_used: 4096,
+        error_message: None,
+        test_case_results,
+    }
+}
+
+#[test]
+fn test_store_submission_and_runs() {
+    let path = temp_db("store");
+    let store = SubmissionStore::open(&path).unwrap();
+
+    assert!(store.create(&submission("s1", "")).unwrap());
+    assert!(!store.create(&submission("s1", "")).unwrap());
+    assert!(store.get("missing").unwrap().is_none());
+
+    let saved = store.get("s1").unwrap().unwrap();
+    assert_eq!(saved.config.language, "python3");
+    assert_eq!(saved.config.time_limit, Duration::from_millis(1000));
+    assert_eq!(saved.test_cases.len(), 1);
+    assert_eq!(saved.test_cases[0].input.to_string(), "1");
+
+    assert!(store.last_run("s1").unwrap().is_none());
+    let first = store
+        .record_run("s1", "", &result(&[JudgeStatus::WrongAnswer]))
+        .unwrap();
+    let second = store
+        .record_run("s1", "", &result(&[JudgeStatus::Accepted]))
+        .unwrap();
+    assert_eq!((first, second), (1, 2));
+
+    // 重新打开后数据仍在
+    drop(store);
+    let store = SubmissionStore::open(&path).unwrap();
+    let runs = store.runs("s1").unwrap();
+    assert_eq!(runs.len(), 2);
+    assert_eq!(runs[0].result.status, JudgeStatus::WrongAnswer);
+    assert_eq!(runs[1].result.time_used, Duration::from_micros(1500));
+    assert_eq!(runs[1].result.test_case_results[0].actual_output, b"out0\n");
+
+    let last = store.last_run("s1").unwrap().unwrap();
+    assert_eq!(last.run, 2);
+    assert_eq!(last.result.status, JudgeStatus::Accepted);
+
+    std::fs::remove_dir_all(path.parent().unwrap()).ok();
+}
+
+#[test]
+fn test_submissions_of_problem() {
+    let path = temp_db("problem");
+    let store = SubmissionStore::open(&path).unwrap();
+    store.create(&submission("a", "p1")).unwrap();
+    store.create(&submission("b", "p2")).unwrap();
+    store.create(&submission("c", "p1")).unwrap();
+
+    assert_eq!(store.submissions_of_problem("p1").unwrap(), vec!["a", "c"]);
+    assert!(store.submissions_of_problem("p3").unwrap().is_empty());
+
+    std::fs::remove_dir_all(path.parent().unwrap()).ok();
+}
+
+#[test]
+fn test_unjudged_submissions() {
+    let path = temp_db("unjudged");
+    let store = SubmissionStore::open(&path).unwrap();
+    store.create(&submission("a", "")).unwrap();
+    store.create(&submission("b", "")).unwrap();
+    store.create(&submission("c", "p1")).unwrap();
+    store
+        .record_run("b", "", &result(&[JudgeStatus::Accepted]))
+        .unwrap();
+
+    assert_eq!(store.unjudged_submissions().unwrap(), vec!["a", "c"]);
+    store
+        .record_run("a", "", &result(&[JudgeStatus::SystemError]))
+        .unwrap();
+    assert_eq!(store.unjudged_submissions().unwrap(), vec!["c"]);
+
+    std::fs::remove_dir_all(path.parent().unwrap()).ok();
+}
+
+#[test]
+fn test_result_diff() {
+    use JudgeStatus::*;
+
+    let first = ResultDiff::new(None, &result(&[Accepted]));
+    assert_eq!(first.previous_status, None);
+    assert!(first.changes.is_empty());
+    assert!(first.changed());
+
+    let same = ResultDiff::new(Some(&result(&[Accepted])), &result(&[Accepted]));
+    assert!(!same.changed());
+
+    // 数据更新后新增测试点，原有测试点结果改变
+    let diff = ResultDiff::new(
+        Some(&result(&[Accepted, Accepted])),
+        &result(&[Accepted, WrongAnswer, TimeLimitExceeded]),
+    );
+    assert_eq!(diff.previous_status, Some(Accepted));
+    assert_eq!(diff.status, WrongAnswer);
+    assert_eq!(
+        diff.changes,
+        vec![
+            TestCaseChange {
+                test_case_id: 1,
+                previous_status: Some(Accepted),
+                status: Some(WrongAnswer),
+            },
+            TestCaseChange {
+                test_case_id: 2,
+                previous_status: None,
+                status: Some(TimeLimitExceeded),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_score() {
+    use JudgeStatus::*;
+
+    let judged = result(&[Accepted, WrongAnswer, Accepted, Accepted]);
+    assert_eq!(judged.score(&[]), 75.0);
+
+    let subtasks = vec![
+        Subtask {
+            name: "1".to_string(),
+            score: 30.0,
+            test_cases: vec![0, 2],
+        },
+        Subtask {
+            name: "2".to_string(),
+            score: 70.0,
+            test_cases: vec![1, 3],
+        },
+    ];
+    assert_eq!(judged.score(&subtasks), 30.0);
+}
+
+#[test]
+fn test_rejudge_report() {
+    use JudgeStatus::*;
+
+    let entry = |id: &str, previous: &[JudgeStatus], current: &[JudgeStatus]| {
+        let previous = result(previous);
+        let current = result(current);
+        RejudgeEntry {
+            submission_id: id.to_string(),
+            run: 2,
+            diff: ResultDiff::new(Some(&previous), &current),
+            previous_score: Some(previous.score(&[])),
+            score: current.score(&[]),
+        }
+    };
+    let report = RejudgeReport {
+        entries: vec![
+            entry("a", &[Accepted, Accepted], &[Accepted, WrongAnswer]),
+            entry("b", &[Accepted, Accepted], &[Accepted, Accepted]),
+            entry("c", &[Accepted, Accepted], &[WrongAnswer, WrongAnswer]),
+            entry("d", &[WrongAnswer, Accepted], &[Accepted, Accepted]),
+            // 最终结果不变，但通过的测试点变少
+            entry("e", &[Accepted, WrongAnswer], &[WrongAnswer, WrongAnswer]),
+        ],
+        failures: vec![("f".to_string(), "not found".to_string())],
+    };
+
+    assert_eq!(
+        report.transitions(),
+        vec![
+            VerdictTransition {
+                previous_status: Accepted,
+                status: WrongAnswer,
+                count: 2,
+            },
+            VerdictTransition {
+                previous_status: WrongAnswer,
+                status: Accepted,
+                count: 1,
+            },
+        ]
+    );
+
+    let affected: Vec<(&str, f64)> = report
+        .affected()
+        .map(|entry| (entry.submission_id.as_str(), entry.score_delta()))
+        .collect();
+    assert_eq!(
+        affected,
+        vec![("a", -50.0), ("c", -100.0), ("d", 50.0), ("e", -50.0)]
+    );
+}
@@ -0,0 +1,123 @@
+use rcgen::{BasicConstraints, CertificateParams, IsCa, KeyPair};
+use std::fs;
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+use tonic::transport::{Certificate, Channel, ClientTlsConfig, Identity};
+use tonic_health::pb::health_check_response::ServingStatus;
+use tonic_health::pb::health_client::HealthClient;
+use tonic_health::pb::HealthCheckRequest;
+
+/// 测试结束时结束服务进程
+struct Server(Child);
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.0.kill().ok();
+        self.0.wait().ok();
+    }
+}
+
+struct Pem {
+    cert: String,
+    key: String,
+}
+
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+async fn check(port: u16, tls: ClientTlsConfig) -> Result<i32, Box<dyn std::error::Error>> {
+    let channel = Channel::from_shared(format!("https://127.0.0.1:{}", port))?
+        .tls_config(tls)?
+        .connect()
+        .await?;
+    let response = HealthClient::new(channel)
+        .check(HealthCheckRequest {
+            service: String::new(),
+        })
+        .await?;
+    Ok(response.into_inner().status)
+}
+
+#[tokio::test]
+async fn test_mtls_requires_client_certificate() {
+    let ca_key = KeyPair::generate().unwrap();
+    let mut ca_params = CertificateParams::new(Vec::<String>::new()).unwrap();
+    ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    let ca = ca_params.self_signed(&ca_key).unwrap();
+
+    let issue = |name: &str| {
+        let key = KeyPair::generate().unwrap();
+        let cert = CertificateParams::new(vec![name.to_string()])
+            .unwrap()
+            .signed_by(&key, &ca, &ca_key)
+            .unwrap();
+        Pem {
+            cert: cert.pem(),
+            key: key.serialize_pem(),
+        }
+    };
+    let server_pem = issue("localhost");
+    let client_pem = issue("backend");
+
+    let dir = std::env::temp_dir().join(format!("opti-neko-tls-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let write = |name: &str, content: &str| -> PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, content).unwrap();
+        path
+    };
+    let ca_path = write("ca.pem", &ca.pem());
+    let cert_path = write("server.pem", &server_pem.cert);
+    let key_path = write("server.key", &server_pem.key);
+
+    let port = free_port();
+    let _server = Server(
+        Command::new(env!("CARGO_BIN_EXE_opti-neko"))
+            .arg("serve")
+            .env("NEKO_SERVER__LISTEN", format!("127.0.0.1:{}", port))
+            .env("NEKO_SERVER__TLS__ENABLED", "true")
+            .env("NEKO_SERVER__TLS__CERT_PATH", &cert_path)
+            .env("NEKO_SERVER__TLS__KEY_PATH", &key_path)
+            .env("NEKO_SERVER__TLS__CLIENT_CA_PATH", &ca_path)
+            .env("NEKO_METRICS__ENABLED", "false")
+            .env("NEKO_STORE__PATH", dir.join("submissions.db"))
+            .env("NEKO_LANGUAGES__ENABLED", "python3")
+            .env("NEKO_HEALTH__LANGUAGES", "python3")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap(),
+    );
+
+    let server_ca = ClientTlsConfig::new()
+        .ca_certificate(Certificate::from_pem(ca.pem()))
+        .domain_name("localhost");
+    let with_identity = server_ca
+        .clone()
+        .identity(Identity::from_pem(&client_pem.cert, &client_pem.key));
+
+    // 等待服务启动并完成首次健康检查
+    let mut status = None;
+    for _ in 0..100 {
+        match check(port, with_identity.clone()).await {
+            Ok(s) if s == ServingStatus::Serving as i32 => {
+                status = Some(s);
+                break;
+            }
+            _ => tokio::time::sleep(Duration::from_millis(200)).await,
+        }
+    }
+    assert_eq!(status, Some(ServingStatus::Serving as i32));
+
+    // 未出示客户端证书的连接被拒绝
+    assert!(check(port, server_ca).await.is_err());
+
+    fs::remove_dir_all(&dir).ok();
+}
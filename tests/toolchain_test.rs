@@ -0,0 +1,39 @@
+use opti_neko::judge::toolchain;
+use std::fs;
+
+#[tokio::test]
+async fn test_self_test_records_languages() {
+    // 读取配置前指定单独的缓存目录，检查自检不会写入编译缓存
+    let cache_dir = std::env::temp_dir().join(format!(
+        "opti-neko-test-toolchain-cache-{}",
+        std::process::id()
+    ));
+    std::env::set_var("NEKO_CACHE__DIR", &cache_dir);
+    assert!(toolchain::languages().is_empty());
+
+    let results = toolchain::self_test().await;
+    assert_eq!(results.len(), 14);
+    assert_eq!(toolchain::languages().len(), results.len());
+
+    for name in ["python3", "cpp17"] {
+        let status = results.iter().find(|s| s.name == name).unwrap();
+        assert!(status.available, "{}: {:?}", name, status.error);
+        assert!(!status.version.is_empty());
+        assert!(!toolchain::is_disabled(name));
+    }
+    assert!(!toolchain::is_disabled("cpp"));
+
+    // 自检未通过的语言被禁用，并记录原因
+    for status in results.iter().filter(|s| !s.available) {
+        assert!(status.error.is_some());
+        assert!(toolchain::is_disabled(status.name));
+    }
+
+    // 无法识别的语言交给评测流程报告编译错误
+    assert!(!toolchain::is_disabled("brainfuck"));
+
+    // 再次自检同样真正调用编译器，不从缓存恢复
+    toolchain::self_test().await;
+    assert_eq!(fs::read_dir(&cache_dir).map_or(0, |dir| dir.count()), 0);
+    fs::remove_dir_all(cache_dir).ok();
+}
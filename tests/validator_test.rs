@@ -0,0 +1,54 @@
+use opti_neko::judge::{TestCase, Validator, ValidatorSpec};
+
+fn test_case(input: &str) -> TestCase {
+    TestCase {
+        input: input.into(),
+        expected_output: "".into(),
+    }
+}
+
+#[tokio::test]
+async fn test_validator_reports_invalid_inputs() {
+    let validator = ValidatorSpec {
+        language: "python3".to_string(),
+        source_code: r#"
+import sys
+data = sys.stdin.read()
+if data.count("\n") != 1 or not data.endswith("\n"):
+    sys.exit("expected exactly one line")
+a, b = map(int, data.split())
+if not (1 <= a <= 100 and 1 <= b <= 100):
+    sys.exit("value out of range [1, 100]")
+"#
+        .to_string(),
+    };
+
+    let test_cases = vec![
+        test_case("1 2\n"),
+        test_case("1 200\n"),
+        test_case("3 4\ngarbage\n"),
+    ];
+
+    let errors = Validator::new(&validator)
+        .validate_all(&test_cases)
+        .await
+        .unwrap();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].test_case_id, 1);
+    assert_eq!(errors[0].message, "value out of range [1, 100]");
+    assert_eq!(errors[1].test_case_id, 2);
+    assert_eq!(errors[1].message, "expected exactly one line");
+}
+
+#[tokio::test]
+async fn test_validator_compilation_error() {
+    let validator = ValidatorSpec {
+        language: "cpp17".to_string(),
+        source_code: "int main() { return }".to_string(),
+    };
+
+    let result = Validator::new(&validator)
+        .validate_all(&[test_case("1\n")])
+        .await;
+    assert!(result.is_err());
+}